@@ -145,13 +145,17 @@ async fn main(_spawner: Spawner) {
                         res.distances()
                     );
                 }
-                if res.calibration_needed() {
-                    log::info!("Calibration needed.");
-                    let calibration = distance.calibrate().await.unwrap();
-                    dynamic_cal_result = distance
-                        .update_calibration(&calibration, &mut buffer)
-                        .await
-                        .unwrap();
+                if distance
+                    .handle_recalibration(
+                        &res,
+                        &mut calibration,
+                        &mut buffer,
+                        &mut dynamic_cal_result,
+                    )
+                    .await
+                    .unwrap()
+                {
+                    log::info!("Recalibrated.");
                 }
             }
             Err(_) => log::error!("Failed to process data."),
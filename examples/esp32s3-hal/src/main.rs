@@ -114,13 +114,17 @@ async fn init(_spawner: Spawner) {
                         res.distances()
                     );
                 }
-                if res.calibration_needed() {
-                    println!("Calibration needed.");
-                    let calibration = distance.calibrate().await.unwrap();
-                    dynamic_cal_result = distance
-                        .update_calibration(&calibration, &mut buffer)
-                        .await
-                        .unwrap();
+                if distance
+                    .handle_recalibration(
+                        &res,
+                        &mut calibration,
+                        &mut buffer,
+                        &mut dynamic_cal_result,
+                    )
+                    .await
+                    .unwrap()
+                {
+                    println!("Recalibrated.");
                 }
             }
             Err(_) => println!("Failed to process data."),
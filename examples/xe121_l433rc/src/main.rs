@@ -108,18 +108,17 @@ async fn main(spawner: Spawner) {
     spawner.spawn(io::button_task(button)).unwrap();
 
     loop {
-        Timer::after(Duration::from_millis(200)).await;
-        'inner: loop {
-            distance
-                .prepare_detector(&calibration, &mut buffer)
-                .unwrap();
-            distance.measure().await.unwrap();
-
-            if let Ok(res) = distance.process_data(
-                &mut buffer,
-                &mut static_call_result,
-                &mut dynamic_call_result,
-            ) {
+        distance
+            .prepare_detector(&calibration, &mut buffer)
+            .unwrap();
+        distance.measure(&mut buffer).await.unwrap();
+
+        match distance.process_data(
+            &mut buffer,
+            &mut static_call_result,
+            &mut dynamic_call_result,
+        ) {
+            Ok(res) => {
                 if res.num_distances() > 0 {
                     info!(
                         "{} Distances found:\n{:?}",
@@ -127,19 +126,21 @@ async fn main(spawner: Spawner) {
                         res.distances()
                     );
                 }
-                if res.calibration_needed() {
-                    info!("Calibration needed");
-                    break 'inner;
+                if distance
+                    .handle_recalibration(
+                        &res,
+                        &mut calibration,
+                        &mut buffer,
+                        &mut dynamic_call_result,
+                    )
+                    .await
+                    .unwrap()
+                {
+                    info!("Recalibrated.");
                 }
-            } else {
-                warn!("Failed to process data");
             }
+            Err(_) => warn!("Failed to process data"),
         }
-        let calibration = distance.calibrate().await.unwrap();
-        dynamic_call_result = distance
-            .update_calibration(&calibration, &mut buffer)
-            .await
-            .unwrap();
     }
 }
 
@@ -16,7 +16,7 @@ use crate::config::subsweep::Subsweep;
 use a121_sys::*;
 
 /// Module for radar configuration errors
-mod error;
+pub mod error;
 /// Module for frame rate values
 pub mod frame_rate;
 /// Module for hardware accelerated average samples (HWAAS) values
@@ -30,6 +30,11 @@ pub mod subsweep;
 
 #[derive(Debug, PartialEq)]
 /// Idle states for the radar sensor between sweeps or frames.
+///
+/// The deeper the state, the more of the sensor hardware is shut down and the longer it takes
+/// to wake up from it. The inter frame idle state must be as deep as or deeper than the inter
+/// sweep idle state. The current drawn in each state depends on the module and supply, see
+/// the A121 datasheet.
 pub enum RadarIdleState {
     /// Deep sleep state for maximum power saving.
     DeepSleep = 0,
@@ -90,6 +95,26 @@ impl RadarConfig {
         }
     }
 
+    /// Creates a configuration for the highest frame rate the sensor can reach.
+    ///
+    /// Uses a single hardware averaged sample, a single sweep per frame, the shortest profile,
+    /// and keeps the sensor ready between frames and sweeps so it never waits to wake up. This
+    /// trades SNR for speed. The frame rate is left unlimited, the achievable rate for the
+    /// remaining settings, such as the number of points, is given by
+    /// [`SparseIqService::min_frame_period`](crate::service::sparse_iq::SparseIqService::min_frame_period).
+    pub fn preset_max_frame_rate() -> Self {
+        let mut config = Self::new();
+        config
+            .set_hwaas(Hwaas::new(1))
+            .expect("HWAAS of 1 is valid");
+        config.set_sweeps_per_frame(1);
+        config.set_profile(RadarProfile::AccProfile1);
+        config.set_frame_rate(FrameRate::Unlimited);
+        config.set_inter_frame_idle_state(RadarIdleState::Ready);
+        config.set_inter_sweep_idle_state(RadarIdleState::Ready);
+        config
+    }
+
     /// Returns a mutable pointer to the internal radar configuration structure
     /// # Safety
     /// This function is unsafe because it returns a raw pointer.
@@ -315,7 +340,12 @@ impl RadarConfig {
 
     /// Set the frame rate
     ///
-    /// Sets the frame rate.
+    /// Sets the frame rate. With a limited frame rate the sensor paces the frames itself,
+    /// [`Radar::measure`](crate::radar::Radar::measure) then completes at the configured rate
+    /// and no delay is needed in the measurement loop. A frame that was not read out in time is
+    /// flagged as delayed in the processed result, see
+    /// [`IqFrame::frame_delayed`](crate::service::sparse_iq::IqFrame::frame_delayed). With an
+    /// unlimited frame rate the rate is set by how fast the frames are read out.
     ///
     /// # Arguments
     ///
@@ -357,6 +387,9 @@ impl RadarConfig {
 
     /// Set inter frame idle state
     ///
+    /// The sensor rests in this state between frames, it must be as deep as or deeper than the
+    /// inter sweep idle state. Read it back with [`RadarConfig::inter_frame_idle_state`].
+    ///
     /// # Arguments
     ///
     /// * `idle_state` - The idle state to use between frames
@@ -379,6 +412,10 @@ impl RadarConfig {
 
     /// Set inter sweep idle state
     ///
+    /// The sensor rests in this state between the sweeps of a frame, so a deeper state only
+    /// saves power if the sweep rate leaves time to wake up. Read it back with
+    /// [`RadarConfig::inter_sweep_idle_state`].
+    ///
     /// # Arguments
     ///
     /// * `idle_state` - The idle state to use between sweeps within a frame
@@ -1,4 +1,4 @@
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, defmt::Format)]
 /// Custom errors for radar configuration operations.
 pub enum ConfigError {
     /// Error indicating invalid hardware accelerated average samples setting.
@@ -9,6 +9,14 @@ pub enum ConfigError {
     SweepRate,
     /// Error indicating invalid number of subsweeps setting.
     NumSubsweep,
-
+    /// Error indicating that the buffer size for the configuration could not be determined.
     BufferSize,
+    /// Error indicating an invalid measurement interval setting.
+    Interval,
+    /// Error indicating an invalid threshold method setting.
+    ThresholdMethod,
+    /// Error indicating an invalid threshold sensitivity setting.
+    ThresholdSensitivity,
+    /// Error indicating an invalid signal quality setting.
+    SignalQuality,
 }
@@ -1,6 +1,8 @@
 use a121_sys::acc_config_profile_t_ACC_CONFIG_PROFILE_1;
+use core::ops::RangeInclusive;
 
-#[derive(Debug, PartialEq, defmt::Format)]
+#[derive(Debug, Clone, Copy, PartialEq, defmt::Format)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// Radar profiles indicating different settings for the sensor's RX and TX paths.
 pub enum RadarProfile {
     /// Profile 1
@@ -27,3 +29,84 @@ impl From<u32> for RadarProfile {
         }
     }
 }
+
+impl RadarProfile {
+    /// Returns the shortest distance in meters the profile can measure at.
+    ///
+    /// The longer pulses of the higher profiles make the direct leakage from the transmitter
+    /// reach further out from the sensor. These are the minimum distances the Acconeer
+    /// distance detector uses for each profile, only profile 1 reaches the sensor itself.
+    pub fn min_distance(self) -> f32 {
+        match self {
+            RadarProfile::AccProfile1 => 0.0,
+            RadarProfile::AccProfile2 => 0.28,
+            RadarProfile::AccProfile3 => 0.56,
+            RadarProfile::AccProfile4 => 0.76,
+            RadarProfile::AccProfile5 => 1.28,
+        }
+    }
+
+    /// Returns the profiles that can measure the whole range in meters, from profile 1 up.
+    ///
+    /// A profile is valid when the range starts no closer than its [`min_distance`]. There is no
+    /// upper limit, although the higher profiles give a better SNR far from the sensor.
+    ///
+    /// [`min_distance`]: RadarProfile::min_distance
+    pub fn valid_for_range(range: &RangeInclusive<f32>) -> impl Iterator<Item = RadarProfile> {
+        let start = *range.start();
+        [
+            RadarProfile::AccProfile1,
+            RadarProfile::AccProfile2,
+            RadarProfile::AccProfile3,
+            RadarProfile::AccProfile4,
+            RadarProfile::AccProfile5,
+        ]
+        .into_iter()
+        .filter(move |profile| start >= profile.min_distance())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn valid_profiles(range: RangeInclusive<f32>) -> Vec<RadarProfile> {
+        RadarProfile::valid_for_range(&range).collect()
+    }
+
+    #[test]
+    fn every_profile_is_valid_far_from_the_sensor() {
+        assert_eq!(
+            valid_profiles(2.0..=5.0),
+            [
+                RadarProfile::AccProfile1,
+                RadarProfile::AccProfile2,
+                RadarProfile::AccProfile3,
+                RadarProfile::AccProfile4,
+                RadarProfile::AccProfile5,
+            ]
+        );
+    }
+
+    #[test]
+    fn only_profile_1_reaches_the_sensor() {
+        assert_eq!(valid_profiles(0.0..=1.0), [RadarProfile::AccProfile1]);
+    }
+
+    #[test]
+    fn the_minimum_distance_of_a_profile_is_inclusive() {
+        assert_eq!(
+            valid_profiles(0.56..=1.0),
+            [
+                RadarProfile::AccProfile1,
+                RadarProfile::AccProfile2,
+                RadarProfile::AccProfile3,
+            ]
+        );
+        assert_eq!(
+            valid_profiles(1.279..=3.0).last(),
+            Some(&RadarProfile::AccProfile4)
+        );
+    }
+}
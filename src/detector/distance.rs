@@ -1,13 +1,17 @@
+#[cfg(feature = "bench")]
+pub mod bench;
 pub mod config;
 pub mod results;
 
 use crate::detector::distance::config::RadarDistanceConfig;
-use crate::detector::distance::results::{DistanceSizes, ProcessDataError};
+use crate::detector::distance::results::{DistanceSizes, ProcessDataError, MAX_NUM_DISTANCES};
+use crate::processing::metadata::min_frame_period;
 use crate::radar::{Radar, Ready};
 use crate::sensor::calibration::CalibrationResult;
 use crate::sensor::error::SensorError;
 use a121_sys::*;
 use core::ffi::c_void;
+use core::time::Duration;
 use defmt::trace;
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::delay::DelayNs;
@@ -40,7 +44,98 @@ impl Drop for InnerRadarDistanceDetector {
     }
 }
 
+/// Counts the measurements started later than the period set by the application.
+#[derive(Debug, Default)]
+struct OverrunTracker {
+    period: Option<Duration>,
+    last: Option<Duration>,
+    count: u32,
+}
+
+impl OverrunTracker {
+    fn set_period(&mut self, period: Option<Duration>) {
+        self.period = period;
+        self.last = None;
+    }
+
+    fn record(&mut self, now: Duration) {
+        if let (Some(period), Some(last)) = (self.period, self.last) {
+            if now.saturating_sub(last) > period {
+                self.count = self.count.saturating_add(1);
+            }
+        }
+        self.last = Some(now);
+    }
+
+    /// Waits with `delay` until a period has passed since the previous measurement, and records
+    /// the measurement about to start.
+    ///
+    /// Returns whether the deadline was missed, the measurement then starts at once and the next
+    /// period counts from `now`. Without a period or a previous measurement nothing is waited for.
+    async fn pace<D: DelayNs>(&mut self, now: Duration, delay: &mut D) -> bool {
+        let (Some(period), Some(last)) = (self.period, self.last) else {
+            self.record(now);
+            return false;
+        };
+        let deadline = last + period;
+        if now < deadline {
+            let wait = deadline - now;
+            delay
+                .delay_us(u32::try_from(wait.as_micros()).unwrap_or(u32::MAX))
+                .await;
+            self.last = Some(deadline);
+            false
+        } else {
+            self.record(now);
+            now > deadline
+        }
+    }
+}
+
+/// Returns the shortest period of the frame processed into `result`.
+///
+/// # Safety
+///
+/// The metadata and sensor configuration of `result` must be null or still valid, that is
+/// `acc_detector_distance_process` must not have been called again since they were set.
+unsafe fn frame_period(result: &acc_detector_distance_result_t) -> Option<Duration> {
+    if result.processing_metadata.is_null() || result.sensor_config.is_null() {
+        return None;
+    }
+    min_frame_period(
+        (*result.processing_metadata).max_sweep_rate,
+        acc_config_sweeps_per_frame_get(result.sensor_config),
+    )
+}
+
+/// Runs `process` again while it fails with [`ProcessDataError::Unavailable`], up to
+/// `max_retries` more times, then fails with [`ProcessDataError::ProcessingFailed`].
+fn retry_unavailable<T>(
+    max_retries: u8,
+    mut process: impl FnMut() -> Result<T, ProcessDataError>,
+) -> Result<T, ProcessDataError> {
+    for _ in 0..=max_retries {
+        match process() {
+            Err(ProcessDataError::Unavailable) => trace!("Result not available, retrying"),
+            result => return result,
+        }
+    }
+    Err(ProcessDataError::ProcessingFailed)
+}
+
+#[cfg(debug_assertions)]
+fn checksum(data: &[u8]) -> u32 {
+    unsafe { acc_alg_basic_util_crc32(data.as_ptr(), data.len()) }
+}
+
 /// The main structure representing the radar distance detector.
+///
+/// The detector only measures when [`RadarDistanceDetector::measure`] is called, there is no
+/// measurement cadence running in the background. Pausing is therefore done by not calling
+/// `measure`: the prepared configuration and the calibration results are kept, and the next
+/// `prepare_detector` and `measure` resume where the session left off. Use
+/// [`Radar::hibernate_on`](crate::radar::Radar::hibernate_on) for longer pauses where the
+/// sensor should also be put in a low power state.
 pub struct RadarDistanceDetector<'radar, SINT, ENABLE, DLY>
 where
     SINT: Wait,
@@ -52,6 +147,12 @@ where
     inner: InnerRadarDistanceDetector,
     /// Configuration for the radar distance detection.
     pub config: RadarDistanceConfig,
+    overruns: OverrunTracker,
+    min_frame_period: Option<Duration>,
+    distance_offset: f32,
+    /// Checksum of the static calibration result, to catch it being overwritten in debug builds
+    #[cfg(debug_assertions)]
+    static_cal_checksum: Option<u32>,
 }
 
 impl<'radar, SINT, ENABLE, DLY> RadarDistanceDetector<'radar, SINT, ENABLE, DLY>
@@ -69,6 +170,11 @@ where
             radar,
             inner,
             config,
+            overruns: OverrunTracker::default(),
+            min_frame_period: None,
+            distance_offset: 0.0,
+            #[cfg(debug_assertions)]
+            static_cal_checksum: None,
         }
     }
 
@@ -83,6 +189,11 @@ where
             radar,
             inner,
             config,
+            overruns: OverrunTracker::default(),
+            min_frame_period: None,
+            distance_offset: 0.0,
+            #[cfg(debug_assertions)]
+            static_cal_checksum: None,
         }
     }
 
@@ -138,9 +249,108 @@ where
                 .expect("Failed to wait for interrupt");
         }
 
+        #[cfg(debug_assertions)]
+        {
+            self.static_cal_checksum = Some(checksum(detector_cal_result_static));
+        }
+
         Ok(detector_cal_result_dynamic)
     }
 
+    /// Applies several configuration changes at once, with a single calibration and prepare.
+    ///
+    /// `change` edits the configuration, the detector is then recreated from it, calibrated and
+    /// prepared once for all the changes. The returned dynamic calibration result and the
+    /// static one written to `detector_cal_result_static` replace the previous ones. The buffer
+    /// sizes may change with the configuration, see
+    /// [`RadarDistanceDetector::get_distance_buffer_size`] and
+    /// [`RadarDistanceDetector::get_static_result_buffer_size`].
+    ///
+    /// The detector is always calibrated again, whichever parameters changed: the SDK copies
+    /// the configuration into the detector when creating it, so a change only takes effect in
+    /// a new detector, and the detector calibration depends on the configuration in ways the
+    /// SDK does not specify per parameter.
+    ///
+    /// Once the new detector is in place, [`RadarDistanceDetector::min_frame_period`] is `None`
+    /// until a frame was processed with it, and the period set with
+    /// [`RadarDistanceDetector::set_frame_period`] is cleared, set it again for the new
+    /// configuration.
+    ///
+    /// On any error the configuration and the detector are restored to what they were before
+    /// the call. Fails with [`SensorError::Config`] if the changed configuration is invalid,
+    /// nothing else is touched then. If the calibration or the prepare fails, the sensor and
+    /// `detector_cal_result_static` may have been used for the new configuration, calibrate and
+    /// prepare the detector again before measuring.
+    pub async fn config_transaction<F>(
+        &mut self,
+        change: F,
+        sensor_cal_result: &CalibrationResult,
+        buffer: &mut [u8],
+        detector_cal_result_static: &mut [u8],
+    ) -> Result<DynamicResult, SensorError>
+    where
+        F: FnOnce(&mut RadarDistanceConfig),
+    {
+        let previous_params = self.config.params();
+        let previous_report_strengths = self.config.strength_reporting();
+        let previous_sensor = self.config.sensor();
+        change(&mut self.config);
+        let result = self
+            .recreate_and_calibrate(sensor_cal_result, buffer, detector_cal_result_static)
+            .await;
+        if result.is_err() {
+            self.config.apply_unchecked(&previous_params);
+            self.config
+                .set_strength_reporting(previous_report_strengths);
+            self.config.sensor_set(previous_sensor);
+        }
+        result
+    }
+
+    async fn recreate_and_calibrate(
+        &mut self,
+        sensor_cal_result: &CalibrationResult,
+        buffer: &mut [u8],
+        detector_cal_result_static: &mut [u8],
+    ) -> Result<DynamicResult, SensorError> {
+        self.config.validate()?;
+        let previous_inner = core::mem::replace(
+            &mut self.inner,
+            InnerRadarDistanceDetector::new(&self.config),
+        );
+        trace!("{:?}", DistanceSizes::new(&self.inner));
+        #[cfg(debug_assertions)]
+        {
+            self.static_cal_checksum = None;
+        }
+        let result = match self
+            .calibrate_detector(sensor_cal_result, buffer, detector_cal_result_static)
+            .await
+        {
+            Ok(dynamic) => self
+                .prepare_detector(sensor_cal_result, buffer)
+                .map(|()| dynamic),
+            Err(error) => Err(error),
+        };
+        match result {
+            Ok(_) => {
+                // Both periods belong to the previous configuration
+                self.min_frame_period = None;
+                self.overruns.set_period(None);
+            }
+            Err(_) => self.inner = previous_inner,
+        }
+        result
+    }
+
+    /// Returns the maximum number of distances a result can hold.
+    ///
+    /// The limit is fixed by the SDK and does not depend on the configuration, it is also
+    /// available as [`MAX_NUM_DISTANCES`] for sizing arrays at compile time.
+    pub fn max_peaks(&self) -> usize {
+        MAX_NUM_DISTANCES
+    }
+
     /// Returns the size of the buffer needed for static calibration results.
     pub fn get_static_result_buffer_size(&self) -> usize {
         DistanceSizes::new(&self.inner).detector_cal_result_static_size
@@ -202,14 +412,37 @@ where
         }
     }
 
+    /// Recalibrates if `result` reports that calibration is needed.
+    ///
+    /// The sensor is calibrated again and the detector calibration is updated, the new results
+    /// replace `sensor_cal_result` and `detector_cal_result_dynamic` so that the next
+    /// `prepare_detector` and `process_data` use them. Returns whether a recalibration was done.
+    pub async fn handle_recalibration(
+        &mut self,
+        result: &DistanceResult,
+        sensor_cal_result: &mut CalibrationResult,
+        buffer: &mut [u8],
+        detector_cal_result_dynamic: &mut DynamicResult,
+    ) -> Result<bool, SensorError> {
+        if !result.calibration_needed() {
+            return Ok(false);
+        }
+        let calibration = self.calibrate().await?;
+        *detector_cal_result_dynamic = self.update_calibration(&calibration, buffer).await?;
+        *sensor_cal_result = calibration;
+        Ok(true)
+    }
+
     /// Prepares the detector for a measurement operation.
     ///
     /// This function must be called before performing a distance measurement to configure the detector properly.
+    /// Fails with [`SensorError::Config`] if the configuration is invalid.
     pub fn prepare_detector(
         &mut self,
         sensor_cal_result: &CalibrationResult,
         buffer: &mut [u8],
     ) -> Result<(), SensorError> {
+        self.config.validate()?;
         unsafe {
             if acc_detector_distance_prepare(
                 self.inner.inner(),
@@ -233,6 +466,67 @@ where
         self.radar.measure(data).await
     }
 
+    /// Performs a distance measurement like [`RadarDistanceDetector::measure`], recording when
+    /// it was started.
+    ///
+    /// `now` is the current time from any monotonic clock of the application, for instance the
+    /// time since boot. When a period is set with [`RadarDistanceDetector::set_frame_period`],
+    /// a measurement started more than a period after the previous one counts as an overrun.
+    pub async fn measure_at(&mut self, data: &mut [u8], now: Duration) -> Result<(), SensorError> {
+        self.overruns.record(now);
+        self.measure(data).await
+    }
+
+    /// Performs a distance measurement like [`RadarDistanceDetector::measure`], paced at the
+    /// period set with [`RadarDistanceDetector::set_frame_period`].
+    ///
+    /// `now` is the current time as for [`RadarDistanceDetector::measure_at`]. The measurement
+    /// is started one period after the previous one, `delay` waits for the time left. Returns
+    /// whether the deadline was missed, the measurement is then started at once, counted as an
+    /// overrun, and the next period starts from `now`. Without a period the measurement is
+    /// started at once.
+    pub async fn measure_at_frame_rate<D: DelayNs>(
+        &mut self,
+        data: &mut [u8],
+        now: Duration,
+        delay: &mut D,
+    ) -> Result<bool, SensorError> {
+        let missed = self.overruns.pace(now, delay).await;
+        self.measure(data).await?;
+        Ok(missed)
+    }
+
+    /// Prepares, measures and processes until a result is available.
+    ///
+    /// Depending on the configuration, the detector needs several frames before it has a
+    /// result, `process_data` then fails with [`ProcessDataError::Unavailable`]. Processing the
+    /// same frame again does not help then, a new frame has to be measured, so this repeats the
+    /// whole prepare, measure and process sequence up to `max_retries` more times, see
+    /// [`RadarDistanceDetector::process_data_retry`] for a transient unavailable result. Fails with
+    /// [`SensorError::ResultNotAvailable`] if there is still no result after that.
+    pub async fn measure_and_process(
+        &mut self,
+        sensor_cal_result: &CalibrationResult,
+        buffer: &mut [u8],
+        detector_cal_result_static: &mut [u8],
+        detector_cal_result_dynamic: &mut DynamicResult,
+        max_retries: u8,
+    ) -> Result<DistanceResult, SensorError> {
+        for _ in 0..=max_retries {
+            self.prepare_detector(sensor_cal_result, buffer)?;
+            self.measure(buffer).await?;
+            match self.process_data(
+                buffer,
+                detector_cal_result_static,
+                detector_cal_result_dynamic,
+            ) {
+                Err(ProcessDataError::Unavailable) => trace!("Result not available, retrying"),
+                result => return Ok(result?),
+            }
+        }
+        Err(SensorError::ResultNotAvailable)
+    }
+
     /// Calibrates the associated radar asynchronously.
     ///
     /// This function performs a calibration operation on the radar, necessary for accurate distance measurements.
@@ -243,14 +537,28 @@ where
     /// Processes the data collected from a distance measurement operation.
     ///
     /// This function analyzes the raw data collected during a measurement operation, extracting distance information.
+    ///
+    /// In debug builds, panics if `detector_cal_result_static` no longer holds the data written
+    /// by the last `calibrate_detector`, which happens when it is shared with another detector
+    /// or reused as a work buffer.
     pub fn process_data(
         &mut self,
         buffer: &mut [u8],
         detector_cal_result_static: &mut [u8],
         detector_cal_result_dynamic: &mut DynamicResult,
-    ) -> Result<DistanceResult<'_>, ProcessDataError> {
+    ) -> Result<DistanceResult, ProcessDataError> {
+        #[cfg(debug_assertions)]
+        if let Some(expected) = self.static_cal_checksum {
+            debug_assert_eq!(
+                checksum(detector_cal_result_static),
+                expected,
+                "the static calibration result changed since calibrate_detector, \
+                 is it shared with another detector or used as a work buffer?"
+            );
+        }
+
         let mut result_available: bool = false;
-        let mut distance_result = DistanceResult::new(&self.radar.config);
+        let mut distance_result = DistanceResult::new();
         let mut distance_result_ptr: acc_detector_distance_result_t = distance_result.inner();
 
         let process_attempt: bool = unsafe {
@@ -263,7 +571,11 @@ where
                 &mut distance_result_ptr as *mut acc_detector_distance_result_t,
             )
         };
-        distance_result.update_from_detector_result(distance_result_ptr);
+        distance_result.update_from_detector_result(distance_result_ptr, &self.config);
+        if process_attempt {
+            self.min_frame_period = unsafe { frame_period(&distance_result_ptr) };
+        }
+        distance_result.apply_offset(self.distance_offset);
 
         if process_attempt {
             if result_available {
@@ -276,8 +588,239 @@ where
         }
     }
 
+    /// Processes the measured frame like [`RadarDistanceDetector::process_data`], processing it
+    /// again while no result is available.
+    ///
+    /// Some configurations report [`ProcessDataError::Unavailable`] on the first processing after
+    /// a configuration change, this is retried up to `max_retries` more times on the same frame,
+    /// without measuring again. Fails with [`ProcessDataError::ProcessingFailed`] if there is
+    /// still no result after that, and with the error of `process_data` for any other failure.
+    /// When the detector needs several frames for a result, use
+    /// [`RadarDistanceDetector::measure_and_process`] instead.
+    pub fn process_data_retry(
+        &mut self,
+        buffer: &mut [u8],
+        detector_cal_result_static: &mut [u8],
+        detector_cal_result_dynamic: &mut DynamicResult,
+        max_retries: u8,
+    ) -> Result<DistanceResult, ProcessDataError> {
+        retry_unavailable(max_retries, || {
+            self.process_data(
+                buffer,
+                detector_cal_result_static,
+                detector_cal_result_dynamic,
+            )
+        })
+    }
+
+    /// Runs a complete measurement starting from a powered off sensor, and powers it off again.
+    ///
+    /// The sensor is powered on and calibrated, unless `sensor_cal_result` already holds a
+    /// valid calibration, which is then reused. The detector is calibrated, a single frame is
+    /// measured and processed, and the sensor is powered off whether this succeeded or not.
+    /// This suits very low duty cycles where the sensor should draw nothing between readings.
+    ///
+    /// The sensor configuration is lost when it is powered off, only call `one_shot` again
+    /// afterwards, or prepare the sensor before any other use.
+    pub async fn one_shot(
+        &mut self,
+        sensor_cal_result: &mut Option<CalibrationResult>,
+        buffer: &mut [u8],
+        detector_cal_result_static: &mut [u8],
+    ) -> Result<DistanceResult, SensorError> {
+        self.radar.power_on().await;
+        let result = self
+            .one_shot_powered(sensor_cal_result, buffer, detector_cal_result_static)
+            .await;
+        self.radar.power_off().await;
+        result
+    }
+
+    async fn one_shot_powered(
+        &mut self,
+        sensor_cal_result: &mut Option<CalibrationResult>,
+        buffer: &mut [u8],
+        detector_cal_result_static: &mut [u8],
+    ) -> Result<DistanceResult, SensorError> {
+        let sensor_cal_result = match sensor_cal_result {
+            Some(cal) if cal.validate_calibration().is_ok() => cal,
+            _ => sensor_cal_result.insert(self.calibrate().await?),
+        };
+        let mut detector_cal_result_dynamic = self
+            .calibrate_detector(sensor_cal_result, buffer, detector_cal_result_static)
+            .await?;
+        self.prepare_detector(sensor_cal_result, buffer)?;
+        self.measure(buffer).await?;
+        Ok(self.process_data(
+            buffer,
+            detector_cal_result_static,
+            &mut detector_cal_result_dynamic,
+        )?)
+    }
+
+    /// Sets an offset in meters added to every reported distance.
+    ///
+    /// Use it to move the zero of the readings from the sensor to a physical reference, such as
+    /// the face of an enclosure, measured during installation. The offset does not change the
+    /// measured interval of the configuration. Defaults to 0.
+    pub fn set_distance_offset(&mut self, offset: f32) {
+        self.distance_offset = offset;
+    }
+
+    /// Returns the offset in meters added to every reported distance.
+    pub fn distance_offset(&self) -> f32 {
+        self.distance_offset
+    }
+
+    /// Returns the shortest period between two measurements that the sensor can sustain with
+    /// the configuration of the detector.
+    ///
+    /// The period is that of the last processed frame, derived from the maximum sweep rate and
+    /// the number of sweeps per frame of the sensor configuration the detector chose. Returns
+    /// `None` until a frame has been processed with the current configuration, or if the SDK
+    /// reports no maximum sweep rate. Depending on the configuration a result needs several
+    /// frames, see [`RadarDistanceDetector::measure_and_process`].
+    pub fn min_frame_period(&self) -> Option<Duration> {
+        self.min_frame_period
+    }
+
+    /// Sets the period the application intends to measure with, `None` to stop checking it.
+    ///
+    /// The distance detector has no frame rate of its own, measurements happen whenever the
+    /// application calls [`RadarDistanceDetector::measure_at`]. The interval between two of
+    /// these calls is compared to `period` to count the overruns. The next call after this one
+    /// starts a new interval. The period is cleared when the configuration is changed with
+    /// [`RadarDistanceDetector::config_transaction`].
+    pub fn set_frame_period(&mut self, period: Option<Duration>) {
+        self.overruns.set_period(period);
+    }
+
+    /// Returns the number of measurements started later than the period set with
+    /// [`RadarDistanceDetector::set_frame_period`], since the detector was created or
+    /// [`RadarDistanceDetector::reset_overruns`] was called.
+    ///
+    /// A growing count means that the application does not keep up with its intended period,
+    /// for instance under a high CPU load.
+    pub fn overruns(&self) -> u32 {
+        self.overruns.count
+    }
+
+    /// Resets the count of overruns.
+    pub fn reset_overruns(&mut self) {
+        self.overruns.count = 0;
+    }
+
     /// Prints the status of the radar distance detector.
     pub fn print_status(&mut self) {
         self.radar.check_status()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::block_on;
+
+    /// Clock advanced by the delays waited on it.
+    #[derive(Default)]
+    struct FakeClock {
+        now: Duration,
+    }
+
+    impl DelayNs for FakeClock {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.now += Duration::from_nanos(ns.into());
+        }
+    }
+
+    #[test]
+    fn overruns_count_late_measurements() {
+        let mut tracker = OverrunTracker::default();
+        tracker.set_period(Some(Duration::from_millis(100)));
+        for ms in [0, 100, 250, 300, 500] {
+            tracker.record(Duration::from_millis(ms));
+        }
+        assert_eq!(tracker.count, 2);
+    }
+
+    #[test]
+    fn overruns_need_a_period() {
+        let mut tracker = OverrunTracker::default();
+        tracker.record(Duration::from_millis(0));
+        tracker.record(Duration::from_secs(10));
+        assert_eq!(tracker.count, 0);
+
+        tracker.set_period(Some(Duration::from_millis(100)));
+        tracker.record(Duration::from_secs(20));
+        assert_eq!(
+            tracker.count, 0,
+            "the first interval starts after setting the period"
+        );
+    }
+
+    #[test]
+    fn pacing_waits_for_the_period_and_reports_missed_deadlines() {
+        let mut tracker = OverrunTracker::default();
+        let mut clock = FakeClock::default();
+        tracker.set_period(Some(Duration::from_millis(100)));
+        let mut measure_after = |clock: &mut FakeClock, work_ms| {
+            clock.now += Duration::from_millis(work_ms);
+            let missed = block_on(tracker.pace(clock.now, clock));
+            (clock.now.as_millis(), missed)
+        };
+
+        assert_eq!(measure_after(&mut clock, 0), (0, false));
+        assert_eq!(measure_after(&mut clock, 30), (100, false));
+        assert_eq!(measure_after(&mut clock, 100), (200, false));
+        assert_eq!(measure_after(&mut clock, 150), (350, true));
+        assert_eq!(measure_after(&mut clock, 20), (450, false));
+        assert_eq!(tracker.count, 1);
+    }
+
+    #[test]
+    fn pacing_needs_a_period() {
+        let mut tracker = OverrunTracker::default();
+        let mut clock = FakeClock::default();
+        for ms in [0, 10, 20] {
+            clock.now = Duration::from_millis(ms);
+            assert!(!block_on(tracker.pace(clock.now, &mut clock)));
+            assert_eq!(clock.now, Duration::from_millis(ms), "nothing to wait for");
+        }
+    }
+
+    #[test]
+    fn unavailable_results_are_retried() {
+        let mut attempts = 0;
+        let result = retry_unavailable(3, || {
+            attempts += 1;
+            if attempts < 3 {
+                Err(ProcessDataError::Unavailable)
+            } else {
+                Ok(attempts)
+            }
+        });
+        assert!(matches!(result, Ok(3)));
+    }
+
+    #[test]
+    fn retries_are_bounded() {
+        let mut attempts = 0;
+        let result: Result<(), _> = retry_unavailable(2, || {
+            attempts += 1;
+            Err(ProcessDataError::Unavailable)
+        });
+        assert!(matches!(result, Err(ProcessDataError::ProcessingFailed)));
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn other_errors_are_not_retried() {
+        let mut attempts = 0;
+        let result: Result<(), _> = retry_unavailable(2, || {
+            attempts += 1;
+            Err(ProcessDataError::CalibrationNeeded)
+        });
+        assert!(matches!(result, Err(ProcessDataError::CalibrationNeeded)));
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,80 @@
+//! Timing of the distance processing on the target.
+//!
+//! Runs [`RadarDistanceDetector::process_data`] repeatedly on a captured frame and reports the
+//! number of cycles it took, so the highest sustainable frame rate for a configuration can be
+//! measured instead of estimated.
+
+use crate::detector::distance::results::DynamicResult;
+use crate::detector::distance::RadarDistanceDetector;
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+
+/// Source of a free running cycle count, such as the DWT cycle counter on Cortex-M.
+pub trait CycleCounter {
+    /// Returns the current cycle count, wrapping around on overflow.
+    fn cycles(&mut self) -> u32;
+}
+
+/// Cycle counts of the benchmarked runs.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, defmt::Format)]
+pub struct BenchStats {
+    /// Number of runs
+    pub runs: u32,
+    /// Fewest cycles taken by a run
+    pub min: u32,
+    /// Average cycles taken by a run
+    pub avg: u32,
+    /// Most cycles taken by a run
+    pub max: u32,
+}
+
+impl<'radar, SINT, ENABLE, DLY> RadarDistanceDetector<'radar, SINT, ENABLE, DLY>
+where
+    SINT: Wait,
+    ENABLE: OutputPin,
+    DLY: DelayNs,
+{
+    /// Processes `frame` `runs` times and returns the cycles each run took.
+    ///
+    /// `frame` is the content of the distance buffer right after a measurement. It is copied
+    /// into `buffer` before every run, outside of the measured time, since processing works in
+    /// place. The detector must be prepared and calibrated as for `process_data`, whether a
+    /// result was available does not matter for the timing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is smaller than `frame`.
+    pub fn bench_process_data<C: CycleCounter>(
+        &mut self,
+        counter: &mut C,
+        frame: &[u8],
+        buffer: &mut [u8],
+        detector_cal_result_static: &mut [u8],
+        detector_cal_result_dynamic: &mut DynamicResult,
+        runs: u32,
+    ) -> BenchStats {
+        let mut min = u32::MAX;
+        let mut max = 0;
+        let mut total: u64 = 0;
+        for _ in 0..runs {
+            buffer[..frame.len()].copy_from_slice(frame);
+            let start = counter.cycles();
+            let _ = self.process_data(
+                buffer,
+                detector_cal_result_static,
+                detector_cal_result_dynamic,
+            );
+            let cycles = counter.cycles().wrapping_sub(start);
+            min = min.min(cycles);
+            max = max.max(cycles);
+            total += u64::from(cycles);
+        }
+        BenchStats {
+            runs,
+            min: if runs == 0 { 0 } else { min },
+            avg: total.checked_div(u64::from(runs)).unwrap_or(0) as u32,
+            max,
+        }
+    }
+}
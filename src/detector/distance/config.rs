@@ -8,6 +8,7 @@
 
 #![warn(missing_docs)]
 
+use crate::config::error::ConfigError;
 use crate::config::profile::RadarProfile;
 use crate::config::profile::RadarProfile::AccProfile5;
 use a121_sys::*;
@@ -18,8 +19,12 @@ pub type SignalQuality = f32;
 /// Type alias for the threshold sensitivity
 pub type ThresholdSensitivity = f32;
 
+/// End of the close range in meters, in which the direct leakage is cancelled.
+const CLOSE_RANGE_END: f32 = 0.1;
+
 /// Enum representing the reflector shape
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, defmt::Format)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ReflectorShape {
     /// Generic reflector shape
     /// This is the default value and represents any non liquid reflector
@@ -43,6 +48,8 @@ impl From<u32> for ReflectorShape {
 }
 
 /// Enum representing the maximum step length
+#[derive(Debug, Copy, Clone, PartialEq, defmt::Format)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MaxStepLenght {
     /// Uses the step length based on the profile
     ProfileBased,
@@ -51,6 +58,8 @@ pub enum MaxStepLenght {
 }
 
 /// Enum representing the peak sorting method
+#[derive(Debug, Copy, Clone, PartialEq, defmt::Format)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PeakSortingMethod {
     /// Closest peak sorting method
     Amplitude =
@@ -71,6 +80,8 @@ impl From<u32> for PeakSortingMethod {
 }
 
 /// Enum representing the threshold method
+#[derive(Debug, Copy, Clone, PartialEq, defmt::Format)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ThresholdMethod {
     /// Fixed amplitude threshold method
     FixedAmplitude(f32),
@@ -82,12 +93,84 @@ pub enum ThresholdMethod {
     Cfar,
 }
 
+/// Complete set of distance detection parameters.
+///
+/// Applied at once with [`RadarDistanceConfig::apply`], which validates every field before
+/// touching the configuration. The default values are the ones of
+/// [`RadarDistanceConfig::balanced`].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DistanceParams {
+    /// Measurement interval in meters
+    pub interval: RangeInclusive<f32>,
+    /// Maximum step length
+    pub max_step_length: MaxStepLenght,
+    /// Maximum profile
+    pub max_profile: RadarProfile,
+    /// Reflector shape
+    pub reflector_shape: ReflectorShape,
+    /// Peak sorting method
+    pub peak_sorting_method: PeakSortingMethod,
+    /// Threshold method
+    pub threshold_method: ThresholdMethod,
+    /// Threshold sensitivity, between 0.0 and 1.0
+    pub threshold_sensitivity: ThresholdSensitivity,
+    /// Signal quality in dB, between -10.0 and 35.0
+    pub signal_quality: SignalQuality,
+    /// Close range leakage cancellation
+    pub close_range_leakage_cancelation: bool,
+}
+
+impl Default for DistanceParams {
+    fn default() -> Self {
+        Self {
+            interval: 15.0..=17.0,
+            max_step_length: MaxStepLenght::ProfileBased,
+            max_profile: AccProfile5,
+            reflector_shape: ReflectorShape::Generic,
+            peak_sorting_method: PeakSortingMethod::Strength,
+            threshold_method: ThresholdMethod::Cfar,
+            threshold_sensitivity: 0.5,
+            signal_quality: 15.0,
+            close_range_leakage_cancelation: false,
+        }
+    }
+}
+
+impl DistanceParams {
+    /// Checks that every parameter is within its valid range.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let (start, end) = (*self.interval.start(), *self.interval.end());
+        if !start.is_finite() || !end.is_finite() || start >= end {
+            return Err(ConfigError::Interval);
+        }
+        let threshold_valid = match self.threshold_method {
+            ThresholdMethod::FixedAmplitude(value) | ThresholdMethod::FixedStrenght(value) => {
+                value.is_finite()
+            }
+            ThresholdMethod::Recorded(num_frames) => num_frames > 0,
+            ThresholdMethod::Cfar => true,
+        };
+        if !threshold_valid {
+            return Err(ConfigError::ThresholdMethod);
+        }
+        if !(0.0..=1.0).contains(&self.threshold_sensitivity) {
+            return Err(ConfigError::ThresholdSensitivity);
+        }
+        if !(-10.0..=35.0).contains(&self.signal_quality) {
+            return Err(ConfigError::SignalQuality);
+        }
+        Ok(())
+    }
+}
+
 /// Configuration for the radar distance detection.
 ///
 /// This struct encapsulates all the parameters and settings for configuring
 /// the distance detection functionality of the radar.
 pub struct RadarDistanceConfig {
     pub(super) inner: *mut acc_detector_distance_config,
+    report_strengths: bool,
 }
 
 impl Drop for RadarDistanceConfig {
@@ -96,6 +179,29 @@ impl Drop for RadarDistanceConfig {
     }
 }
 
+impl defmt::Format for RadarDistanceConfig {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "RadarDistanceConfig {{ interval: {}..={}, max_step_length: {}, max_profile: {}, \
+             reflector_shape: {}, peak_sorting_method: {}, threshold_method: {}, \
+             threshold_sensitivity: {}, signal_quality: {}, \
+             close_range_leakage_cancelation: {}, report_strengths: {} }}",
+            self.start_interval(),
+            self.end_interval(),
+            self.max_step_length(),
+            self.max_profile(),
+            self.reflector_shape(),
+            self.peak_sorting_method(),
+            self.threshold_method(),
+            self.threshold_sensitivity(),
+            self.signal_quality(),
+            self.close_range_leakage_cancelation(),
+            self.report_strengths,
+        )
+    }
+}
+
 impl Default for RadarDistanceConfig {
     fn default() -> Self {
         Self::balanced()
@@ -107,35 +213,91 @@ impl RadarDistanceConfig {
     fn new() -> Self {
         Self {
             inner: unsafe { acc_detector_distance_config_create() },
+            report_strengths: true,
         }
     }
 
+    /// Returns a builder for a configuration, starting from the balanced parameters.
+    pub fn builder() -> RadarDistanceConfigBuilder {
+        RadarDistanceConfigBuilder::default()
+    }
+
     /// Create a balanced distance detection configuration.
     pub fn balanced() -> Self {
         let mut config = Self::new();
-        config.set_interval(15.0..=17.0);
-        config.set_max_step_length(MaxStepLenght::ProfileBased);
-        config.set_max_profile(AccProfile5);
-        config.set_reflector_shape(ReflectorShape::Generic);
-        config.set_peak_sorting_method(PeakSortingMethod::Strength);
-        config.set_threshold_method(ThresholdMethod::Cfar);
-        config.set_threshold_sensitivity(0.5);
-        config.set_signal_quality(15.0);
-        config.set_close_range_leakage_cancelation(false);
+        config.apply_unchecked(&DistanceParams::default());
         config
     }
 
+    /// Applies a complete set of parameters.
+    ///
+    /// All parameters are validated first, the configuration is left untouched if any of them
+    /// is invalid.
+    pub fn apply(&mut self, params: &DistanceParams) -> Result<(), ConfigError> {
+        params.validate()?;
+        self.apply_unchecked(params);
+        Ok(())
+    }
+
+    /// Checks that the parameters currently set are valid, see [`DistanceParams::validate`].
+    ///
+    /// Called by `prepare_detector`, so that an invalid configuration is reported with its
+    /// reason rather than as a failed prepare.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.params().validate()
+    }
+
+    /// Returns the parameters currently set, as applied by [`RadarDistanceConfig::apply`].
+    pub fn params(&self) -> DistanceParams {
+        DistanceParams {
+            interval: self.interval(),
+            max_step_length: match self.max_step_length() {
+                0 => MaxStepLenght::ProfileBased,
+                length => MaxStepLenght::Manual(length),
+            },
+            max_profile: self.max_profile(),
+            reflector_shape: self.reflector_shape(),
+            peak_sorting_method: self.peak_sorting_method(),
+            threshold_method: self.threshold_method(),
+            threshold_sensitivity: self.threshold_sensitivity(),
+            signal_quality: self.signal_quality(),
+            close_range_leakage_cancelation: self.close_range_leakage_cancelation(),
+        }
+    }
+
+    pub(super) fn apply_unchecked(&mut self, params: &DistanceParams) {
+        self.set_interval(params.interval.clone());
+        self.set_max_step_length(params.max_step_length);
+        self.set_max_profile(params.max_profile);
+        self.set_reflector_shape(params.reflector_shape);
+        self.set_peak_sorting_method(params.peak_sorting_method);
+        self.set_threshold_method(params.threshold_method);
+        self.set_threshold_sensitivity(params.threshold_sensitivity);
+        self.set_signal_quality(params.signal_quality);
+        self.set_close_range_leakage_cancelation(params.close_range_leakage_cancelation);
+    }
+
     /// Sets the sensor ID to be used for detection.
     pub fn sensor_set(&mut self, sensor_id: u32) {
         unsafe { acc_detector_distance_config_sensor_set(self.inner, sensor_id) }
     }
 
+    /// Returns the sensor ID used for detection.
+    pub fn sensor(&self) -> u32 {
+        unsafe { acc_detector_distance_config_sensor_get(self.inner) }
+    }
+
     /// Configures the measurement interval in meters.
     pub fn set_interval(&mut self, range: RangeInclusive<f32>) {
         self.set_start_interval(*range.start());
         self.set_end_interval(*range.end());
     }
 
+    /// Returns the measurement interval in meters.
+    pub fn interval(&self) -> RangeInclusive<f32> {
+        self.start_interval()..=self.end_interval()
+    }
+
     /// Sets the start of the measurement interval in meters.
     pub fn set_start_interval(&mut self, start_interval: f32) {
         unsafe { acc_detector_distance_config_start_set(self.inner, start_interval) }
@@ -187,7 +349,23 @@ impl RadarDistanceConfig {
         unsafe { acc_detector_distance_config_close_range_leakage_cancellation_get(self.inner) }
     }
 
+    /// Returns whether the detector calibration includes the close range leakage calibration.
+    ///
+    /// The leakage is characterized when close range leakage cancellation is enabled and the
+    /// interval starts within 100mm of the sensor, which makes the calibration take longer and
+    /// requires free space in front of the sensor while it runs.
+    pub fn requires_leakage_calibration(&self) -> bool {
+        self.close_range_leakage_cancelation() && self.start_interval() < CLOSE_RANGE_END
+    }
+
     /// Sets the signal quality in dB.
+    ///
+    /// A higher signal quality gives a better SNR through a higher HWAAS, at the cost of a
+    /// higher power consumption and a longer measurement. The value is relative, it is not the SNR
+    /// of the measured object, which also depends on its size, material and distance. Averaging
+    /// twice as many samples improves the SNR by about 3 dB, so raising the signal quality by
+    /// 3 dB roughly doubles the HWAAS and the time spent measuring. The default of 15.0 suits
+    /// most use cases.
     pub fn set_signal_quality(&mut self, signal_quality: SignalQuality) {
         unsafe {
             acc_detector_distance_config_signal_quality_set(
@@ -277,6 +455,12 @@ impl RadarDistanceConfig {
     }
 
     /// Sets the reflector shape.
+    ///
+    /// The shape selects how the reported strengths are compensated for the distance to the
+    /// reflector: a generic reflector falls off with the fourth power of the distance, a planar
+    /// one with the square. The same target therefore reports different strengths with the two
+    /// shapes, only compare strengths measured with the same shape. The amplitude before this
+    /// compensation is not reported by the SDK.
     pub fn set_reflector_shape(&mut self, shape: ReflectorShape) {
         unsafe { acc_detector_distance_config_reflector_shape_set(self.inner, shape as u32) }
     }
@@ -285,4 +469,364 @@ impl RadarDistanceConfig {
     pub fn reflector_shape(&self) -> ReflectorShape {
         unsafe { acc_detector_distance_config_reflector_shape_get(self.inner) }.into()
     }
+
+    /// Enables or disables the reporting of the strength of each detected distance.
+    ///
+    /// The detector always computes the strengths, disabling the reporting skips copying them
+    /// into the result, whose strength accessors are then empty. Enabled by default.
+    pub fn set_strength_reporting(&mut self, enable: bool) {
+        self.report_strengths = enable;
+    }
+
+    /// Returns whether the strength of each detected distance is reported.
+    pub fn strength_reporting(&self) -> bool {
+        self.report_strengths
+    }
+}
+
+/// Builder for a [`RadarDistanceConfig`].
+///
+/// Collects the parameters and applies them at once in [`build`], which validates them like
+/// [`RadarDistanceConfig::apply`]. Unset parameters keep the values of
+/// [`RadarDistanceConfig::balanced`].
+///
+/// [`build`]: RadarDistanceConfigBuilder::build
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadarDistanceConfigBuilder {
+    params: DistanceParams,
+    report_strengths: bool,
+}
+
+impl Default for RadarDistanceConfigBuilder {
+    fn default() -> Self {
+        Self {
+            params: DistanceParams::default(),
+            report_strengths: true,
+        }
+    }
+}
+
+impl RadarDistanceConfigBuilder {
+    /// Starts from the given parameters.
+    pub fn from_params(params: DistanceParams) -> Self {
+        Self {
+            params,
+            ..Self::default()
+        }
+    }
+
+    /// Sets the measurement interval in meters.
+    pub fn interval(mut self, range: RangeInclusive<f32>) -> Self {
+        self.params.interval = range;
+        self
+    }
+
+    /// Sets the maximum step length.
+    pub fn max_step_length(mut self, max_step_length: MaxStepLenght) -> Self {
+        self.params.max_step_length = max_step_length;
+        self
+    }
+
+    /// Sets the maximum profile to use.
+    pub fn max_profile(mut self, max_profile: RadarProfile) -> Self {
+        self.params.max_profile = max_profile;
+        self
+    }
+
+    /// Sets the reflector shape.
+    pub fn reflector_shape(mut self, shape: ReflectorShape) -> Self {
+        self.params.reflector_shape = shape;
+        self
+    }
+
+    /// Sets the peak sorting method.
+    pub fn peak_sorting_method(mut self, method: PeakSortingMethod) -> Self {
+        self.params.peak_sorting_method = method;
+        self
+    }
+
+    /// Sets the threshold method with the given parameters.
+    pub fn threshold_method(mut self, method: ThresholdMethod) -> Self {
+        self.params.threshold_method = method;
+        self
+    }
+
+    /// Sets the threshold sensitivity, between 0.0 and 1.0.
+    pub fn threshold_sensitivity(mut self, sensitivity: ThresholdSensitivity) -> Self {
+        self.params.threshold_sensitivity = sensitivity;
+        self
+    }
+
+    /// Sets the signal quality in dB, between -10.0 and 35.0.
+    pub fn signal_quality(mut self, signal_quality: SignalQuality) -> Self {
+        self.params.signal_quality = signal_quality;
+        self
+    }
+
+    /// Enables or disables close range leakage cancellation.
+    pub fn close_range_leakage_cancelation(mut self, enable: bool) -> Self {
+        self.params.close_range_leakage_cancelation = enable;
+        self
+    }
+
+    /// Enables or disables the reporting of the strength of each detected distance.
+    pub fn strength_reporting(mut self, enable: bool) -> Self {
+        self.report_strengths = enable;
+        self
+    }
+
+    /// Returns the parameters collected so far.
+    pub fn params(&self) -> &DistanceParams {
+        &self.params
+    }
+
+    /// Checks the parameters collected so far, see [`DistanceParams::validate`].
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.params.validate()
+    }
+
+    /// Creates the configuration.
+    ///
+    /// Fails without creating a configuration if any parameter is invalid, see
+    /// [`RadarDistanceConfigBuilder::validate`].
+    pub fn build(&self) -> Result<RadarDistanceConfig, ConfigError> {
+        self.validate()?;
+        let mut config = RadarDistanceConfig::new();
+        config.apply_unchecked(&self.params);
+        config.set_strength_reporting(self.report_strengths);
+        Ok(config)
+    }
+}
+
+/// Serialized form of [`RadarDistanceConfig`].
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct SerializedConfig {
+    params: DistanceParams,
+    report_strengths: bool,
+}
+
+/// Serializes the parameters of the configuration, see [`RadarDistanceConfig::params`].
+#[cfg(feature = "serde")]
+impl serde::Serialize for RadarDistanceConfig {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        SerializedConfig {
+            params: self.params(),
+            report_strengths: self.report_strengths,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes the parameters and applies them to a new configuration, invalid parameters are
+/// rejected as [`RadarDistanceConfig::apply`] does.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for RadarDistanceConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let serialized = SerializedConfig::deserialize(deserializer)?;
+        let mut config = Self::new();
+        config.apply(&serialized.params).map_err(|error| {
+            serde::de::Error::custom(format_args!("invalid parameter: {:?}", error))
+        })?;
+        config.set_strength_reporting(serialized.report_strengths);
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_params_are_valid() {
+        assert_eq!(DistanceParams::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_empty_or_non_finite_intervals() {
+        for interval in [
+            2.0..=1.0,
+            1.0..=1.0,
+            f32::NAN..=1.0,
+            0.5..=f32::NAN,
+            0.5..=f32::INFINITY,
+        ] {
+            let params = DistanceParams {
+                interval: interval.clone(),
+                ..DistanceParams::default()
+            };
+            assert_eq!(
+                params.validate(),
+                Err(ConfigError::Interval),
+                "{interval:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn validate_checks_the_threshold_of_each_method() {
+        for (method, valid) in [
+            (ThresholdMethod::FixedAmplitude(100.0), true),
+            (ThresholdMethod::FixedAmplitude(f32::NAN), false),
+            (ThresholdMethod::FixedStrenght(-5.0), true),
+            (ThresholdMethod::FixedStrenght(f32::INFINITY), false),
+            (ThresholdMethod::Recorded(20), true),
+            (ThresholdMethod::Recorded(0), false),
+            (ThresholdMethod::Cfar, true),
+        ] {
+            let params = DistanceParams {
+                threshold_method: method,
+                ..DistanceParams::default()
+            };
+            let expected = if valid {
+                Ok(())
+            } else {
+                Err(ConfigError::ThresholdMethod)
+            };
+            assert_eq!(params.validate(), expected, "{method:?}");
+        }
+    }
+
+    #[test]
+    fn validate_bounds_the_threshold_sensitivity() {
+        for (sensitivity, valid) in [
+            (0.0, true),
+            (1.0, true),
+            (-0.1, false),
+            (1.1, false),
+            (f32::NAN, false),
+        ] {
+            let params = DistanceParams {
+                threshold_sensitivity: sensitivity,
+                ..DistanceParams::default()
+            };
+            let expected = if valid {
+                Ok(())
+            } else {
+                Err(ConfigError::ThresholdSensitivity)
+            };
+            assert_eq!(params.validate(), expected, "{sensitivity}");
+        }
+    }
+
+    #[test]
+    fn validate_bounds_the_signal_quality() {
+        for (signal_quality, valid) in [
+            (-10.0, true),
+            (35.0, true),
+            (-10.5, false),
+            (35.5, false),
+            (f32::NAN, false),
+        ] {
+            let params = DistanceParams {
+                signal_quality,
+                ..DistanceParams::default()
+            };
+            let expected = if valid {
+                Ok(())
+            } else {
+                Err(ConfigError::SignalQuality)
+            };
+            assert_eq!(params.validate(), expected, "{signal_quality}");
+        }
+    }
+
+    #[test]
+    fn builder_collects_every_parameter() {
+        let builder = RadarDistanceConfig::builder()
+            .interval(0.5..=3.0)
+            .max_step_length(MaxStepLenght::Manual(12))
+            .max_profile(RadarProfile::AccProfile3)
+            .reflector_shape(ReflectorShape::Planar)
+            .peak_sorting_method(PeakSortingMethod::Amplitude)
+            .threshold_method(ThresholdMethod::Recorded(20))
+            .threshold_sensitivity(0.8)
+            .signal_quality(20.0)
+            .close_range_leakage_cancelation(true);
+
+        let expected = DistanceParams {
+            interval: 0.5..=3.0,
+            max_step_length: MaxStepLenght::Manual(12),
+            max_profile: RadarProfile::AccProfile3,
+            reflector_shape: ReflectorShape::Planar,
+            peak_sorting_method: PeakSortingMethod::Amplitude,
+            threshold_method: ThresholdMethod::Recorded(20),
+            threshold_sensitivity: 0.8,
+            signal_quality: 20.0,
+            close_range_leakage_cancelation: true,
+        };
+        assert_eq!(builder.params(), &expected);
+        assert_eq!(RadarDistanceConfigBuilder::from_params(expected), builder);
+    }
+
+    #[test]
+    fn builder_starts_from_the_balanced_parameters() {
+        let builder = RadarDistanceConfigBuilder::default();
+        assert_eq!(builder.params(), &DistanceParams::default());
+        assert_eq!(builder.validate(), Ok(()));
+        assert_eq!(
+            builder,
+            RadarDistanceConfig::builder().strength_reporting(true)
+        );
+    }
+
+    #[test]
+    fn builder_validate_rejects_invalid_parameters() {
+        let cases = [
+            (
+                RadarDistanceConfig::builder().interval(3.0..=0.5),
+                ConfigError::Interval,
+            ),
+            (
+                RadarDistanceConfig::builder().threshold_method(ThresholdMethod::Recorded(0)),
+                ConfigError::ThresholdMethod,
+            ),
+            (
+                RadarDistanceConfig::builder().threshold_sensitivity(1.5),
+                ConfigError::ThresholdSensitivity,
+            ),
+            (
+                RadarDistanceConfig::builder().signal_quality(f32::NAN),
+                ConfigError::SignalQuality,
+            ),
+        ];
+        for (builder, error) in cases {
+            assert_eq!(builder.validate(), Err(error));
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn params_round_trip_through_json() {
+        let params = DistanceParams {
+            interval: 0.5..=3.0,
+            max_step_length: MaxStepLenght::Manual(12),
+            max_profile: RadarProfile::AccProfile3,
+            reflector_shape: ReflectorShape::Planar,
+            peak_sorting_method: PeakSortingMethod::Amplitude,
+            threshold_method: ThresholdMethod::FixedAmplitude(150.0),
+            threshold_sensitivity: 0.8,
+            signal_quality: 20.0,
+            close_range_leakage_cancelation: true,
+        };
+        let json = serde_json::to_string(&params).unwrap();
+        assert_eq!(
+            serde_json::from_str::<DistanceParams>(&json).unwrap(),
+            params
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn enums_serialize_by_name() {
+        let json = serde_json::to_value(DistanceParams::default()).unwrap();
+        assert_eq!(json["max_profile"], "AccProfile5");
+        assert_eq!(json["reflector_shape"], "Generic");
+        assert_eq!(json["peak_sorting_method"], "Strength");
+        assert_eq!(json["threshold_method"], "Cfar");
+        assert_eq!(json["max_step_length"], "ProfileBased");
+
+        let json = serde_json::to_value(ThresholdMethod::Recorded(20)).unwrap();
+        assert_eq!(json, serde_json::json!({ "Recorded": 20 }));
+    }
 }
@@ -1,11 +1,16 @@
-use crate::config::RadarConfig;
+use crate::detector::distance::config::RadarDistanceConfig;
 use crate::detector::distance::InnerRadarDistanceDetector;
 use crate::processing::metadata::ProcessingMetaData;
 use crate::processing::ProcessingResult;
+use crate::sensor::error::SensorError;
 use a121_sys::{
     acc_detector_cal_result_dynamic_t, acc_detector_distance_get_sizes,
     acc_detector_distance_result_t, ACC_DETECTOR_DISTANCE_RESULT_MAX_NUM_DISTANCES,
 };
+use core::fmt::{self, Write};
+
+/// Maximum number of distances the SDK reports in a single result, for any configuration.
+pub const MAX_NUM_DISTANCES: usize = ACC_DETECTOR_DISTANCE_RESULT_MAX_NUM_DISTANCES as usize;
 
 /// Enumerates possible errors that can occur during the processing of radar data.
 #[derive(Debug, Copy, Clone, defmt::Format)]
@@ -15,7 +20,20 @@ pub enum ProcessDataError {
     Unavailable,
 }
 
+impl From<ProcessDataError> for SensorError {
+    fn from(error: ProcessDataError) -> Self {
+        match error {
+            ProcessDataError::CalibrationNeeded => SensorError::CalibrationInvalid,
+            ProcessDataError::ProcessingFailed => SensorError::ProcessingFailed,
+            ProcessDataError::Unavailable => SensorError::ResultNotAvailable,
+        }
+    }
+}
+
 /// Represents a single detected distance and its strength.
+///
+/// The distance is in meters. The strength is the reflectivity of the detected object, it is
+/// left at 0.0 if strength reporting is disabled in the distance configuration.
 #[derive(Debug, Default, Copy, Clone, defmt::Format)]
 pub struct Distance {
     pub distance: f32,
@@ -26,29 +44,34 @@ pub struct Distance {
 ///
 /// This struct contains the distances detected by the radar, along with metadata
 /// such as the temperature during the detection and whether calibration is needed.
-pub struct DistanceResult<'a> {
+/// The distances are stored inline, up to [`MAX_NUM_DISTANCES`], so the result does not
+/// allocate.
+pub struct DistanceResult {
     result: ProcessingResult,
     metadata: ProcessingMetaData,
-    radar_config: &'a RadarConfig,
-    distances: [Distance; ACC_DETECTOR_DISTANCE_RESULT_MAX_NUM_DISTANCES as usize],
+    distances: [Distance; MAX_NUM_DISTANCES],
     num_distances: u8,
+    strengths_reported: bool,
+    interval_start: f32,
+    interval_end: f32,
     near_start_edge_status: bool,
     calibration_needed: bool,
     temperature: i16,
 }
 
-impl<'a> DistanceResult<'a> {
+impl DistanceResult {
     /// Creates a new instance of `DistanceResult`.
-    pub fn new(config: &'a RadarConfig) -> Self {
+    pub fn new() -> Self {
         let proc_result = ProcessingResult::new();
         let proc_metadata = ProcessingMetaData::new();
         Self {
             result: proc_result,
             metadata: proc_metadata,
-            radar_config: config,
-            distances: [Distance::default();
-                ACC_DETECTOR_DISTANCE_RESULT_MAX_NUM_DISTANCES as usize],
+            distances: [Distance::default(); MAX_NUM_DISTANCES],
             num_distances: 0,
+            strengths_reported: false,
+            interval_start: 0.0,
+            interval_end: 0.0,
             near_start_edge_status: false,
             calibration_needed: false,
             temperature: 0,
@@ -57,35 +80,103 @@ impl<'a> DistanceResult<'a> {
 
     pub(super) fn inner(&mut self) -> acc_detector_distance_result_t {
         acc_detector_distance_result_t {
-            distances: [0.0; ACC_DETECTOR_DISTANCE_RESULT_MAX_NUM_DISTANCES as usize],
-            strengths: [0.0; ACC_DETECTOR_DISTANCE_RESULT_MAX_NUM_DISTANCES as usize],
+            distances: [0.0; MAX_NUM_DISTANCES],
+            strengths: [0.0; MAX_NUM_DISTANCES],
             num_distances: 0,
             near_start_edge_status: false,
             calibration_needed: false,
             temperature: 0,
             processing_result: unsafe { self.result.mut_ptr() },
             processing_metadata: unsafe { self.metadata.mut_ptr() },
-            sensor_config: self.radar_config.ptr(),
+            sensor_config: core::ptr::null(),
         }
     }
 
-    pub(super) fn update_from_detector_result(&mut self, inner: acc_detector_distance_result_t) {
+    pub(super) fn update_from_detector_result(
+        &mut self,
+        inner: acc_detector_distance_result_t,
+        config: &RadarDistanceConfig,
+    ) {
+        let report_strengths = config.strength_reporting();
         self.num_distances = inner.num_distances;
+        self.strengths_reported = report_strengths;
+        self.interval_start = config.start_interval();
+        self.interval_end = config.end_interval();
         for i in 0..inner.num_distances as usize {
             self.distances[i].distance = inner.distances[i];
-            self.distances[i].strength = inner.strengths[i];
+            if report_strengths {
+                self.distances[i].strength = inner.strengths[i];
+            }
         }
         self.near_start_edge_status = inner.near_start_edge_status;
         self.calibration_needed = inner.calibration_needed;
         self.temperature = inner.temperature;
     }
 
-    /// Returns the detected distances.
+    /// Adds `offset` meters to the detected distances and to the measured interval.
+    pub(super) fn apply_offset(&mut self, offset: f32) {
+        for d in &mut self.distances[..self.num_distances as usize] {
+            d.distance += offset;
+        }
+        self.interval_start += offset;
+        self.interval_end += offset;
+    }
+
+    /// Returns the detected distances, each with its strength when strength reporting is
+    /// enabled.
+    ///
+    /// Empty if nothing was detected. The order follows the peak sorting method of the distance
+    /// configuration.
     pub fn distances(&self) -> &[Distance] {
         &self.distances[0..self.num_distances as usize]
     }
 
-    /// Returns the near start edge status.
+    /// Returns the strengths of the detected distances, in the same order as the distances.
+    ///
+    /// Empty if strength reporting is disabled in the distance configuration. Strengths depend on
+    /// the configured reflector shape, see [`RadarDistanceConfig::set_reflector_shape`].
+    pub fn strengths(&self) -> impl Iterator<Item = f32> + '_ {
+        let count = if self.strengths_reported {
+            self.num_distances as usize
+        } else {
+            0
+        };
+        self.distances[..count].iter().map(|d| d.strength)
+    }
+
+    /// Returns the distance in meters between the detected distance at `index` and the closest
+    /// edge of the measured interval, or `None` if there is no detected distance at `index`.
+    ///
+    /// Reflections sitting at the very start or end of the interval are only partially covered
+    /// by the measurement and are less reliable than the ones well inside it.
+    pub fn edge_distance(&self, index: usize) -> Option<f32> {
+        let distance = self.distances().get(index)?.distance;
+        Some((distance - self.interval_start).min(self.interval_end - distance))
+    }
+
+    /// Writes the result as one CSV line, `timestamp,num,dist0,str0,dist1,str1,...`, ending with
+    /// a newline.
+    ///
+    /// `timestamp` is written as given, in whatever unit the caller uses. Distances are in
+    /// meters, the strength fields are left empty if strength reporting is disabled. The column
+    /// order is stable so host side parsers can rely on it.
+    pub fn write_csv_line<W: Write>(&self, w: &mut W, timestamp: u64) -> fmt::Result {
+        write!(w, "{},{}", timestamp, self.num_distances)?;
+        for d in self.distances() {
+            write!(w, ",{}", d.distance)?;
+            if self.strengths_reported {
+                write!(w, ",{}", d.strength)?;
+            } else {
+                w.write_char(',')?;
+            }
+        }
+        w.write_char('\n')
+    }
+
+    /// Returns whether there might be an object near the start of the measured interval.
+    ///
+    /// Such an object may partly lie before the start of the interval and then not be reported
+    /// in the distances, the status can be set with no distance detected.
     pub fn near_start_edge_status(&self) -> bool {
         self.near_start_edge_status
     }
@@ -95,7 +186,10 @@ impl<'a> DistanceResult<'a> {
         self.calibration_needed
     }
 
-    /// Returns the temperature during the detection.
+    /// Returns the temperature of the sensor during the detection, in degrees Celsius.
+    ///
+    /// The absolute accuracy is poor, use it to follow temperature changes, such as comparing it
+    /// with the temperature of the calibration to decide when to calibrate again.
     pub fn temperature(&self) -> i16 {
         self.temperature
     }
@@ -116,6 +210,26 @@ impl<'a> DistanceResult<'a> {
     }
 }
 
+impl defmt::Format for DistanceResult {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "DistanceResult {{ distances: {}, near_start_edge_status: {}, \
+             calibration_needed: {}, temperature: {} }}",
+            self.distances(),
+            self.near_start_edge_status,
+            self.calibration_needed,
+            self.temperature,
+        )
+    }
+}
+
+impl Default for DistanceResult {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// Represents the dynamic part of the detector calibration result.
 ///
 /// This struct encapsulates the dynamic calibration data that may need to be updated
@@ -160,3 +274,49 @@ impl DistanceSizes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::string::String;
+
+    fn result(distances: &[(f32, f32)], strengths_reported: bool) -> DistanceResult {
+        let mut result = DistanceResult::new();
+        for (d, &(distance, strength)) in result.distances.iter_mut().zip(distances) {
+            *d = Distance { distance, strength };
+        }
+        result.num_distances = distances.len() as u8;
+        result.strengths_reported = strengths_reported;
+        result
+    }
+
+    fn csv_line(result: &DistanceResult, timestamp: u64) -> String {
+        let mut line = String::new();
+        result.write_csv_line(&mut line, timestamp).unwrap();
+        line
+    }
+
+    #[test]
+    fn csv_line_lists_each_distance_with_its_strength() {
+        let result = result(&[(0.5, 12.25), (1.75, -3.5)], true);
+        assert_eq!(csv_line(&result, 1234), "1234,2,0.5,12.25,1.75,-3.5\n");
+    }
+
+    #[test]
+    fn csv_line_leaves_strengths_empty_when_not_reported() {
+        let result = result(&[(0.5, 0.0), (1.75, 0.0)], false);
+        assert_eq!(csv_line(&result, 7), "7,2,0.5,,1.75,\n");
+    }
+
+    #[test]
+    fn csv_line_without_distances() {
+        assert_eq!(csv_line(&result(&[], true), 0), "0,0\n");
+    }
+
+    #[test]
+    fn temperature_is_reported() {
+        let mut result = result(&[], false);
+        result.temperature = -12;
+        assert_eq!(result.temperature(), -12);
+    }
+}
@@ -1,19 +1,44 @@
-use core::cell::RefCell;
+#[cfg(feature = "nightly-logger")]
+use alloc::string::String;
+use core::cell::{Cell, RefCell};
 use core::ffi::{c_char, c_void, CStr};
 use defmt::trace;
 
 use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
 use embassy_sync::blocking_mutex::Mutex;
-use embedded_hal::spi::{ErrorKind as SpiErrorKind, SpiDevice};
+use embedded_hal::spi::{ErrorKind as SpiErrorKind, Mode, SpiDevice, MODE_0};
+
+#[cfg(feature = "async-spi")]
+pub mod async_spi;
 
-use a121_sys::{acc_hal_a121_t, acc_hal_optimization_t, acc_rss_hal_register, acc_sensor_id_t};
+use a121_sys::{
+    acc_hal_a121_t, acc_hal_optimization_t, acc_rss_hal_register, acc_sensor_id_t,
+    ACC_HAL_SPI_TRANSFER_SIZE_REQUIRED,
+};
 
 pub type RadarSpi = dyn SpiDevice<u8, Error = SpiErrorKind> + Send;
 pub type RefRadarSpi = &'static mut RadarSpi;
+pub type RadarSpi16 = dyn SpiDevice<u16, Error = SpiErrorKind> + Send;
+pub type RefRadarSpi16 = &'static mut RadarSpi16;
+
+/// SPI mode to configure the SPI device of the sensor with, clock idle low and data sampled on
+/// the rising edge.
+///
+/// A bus set up with another mode may still appear to work for some transfers, with the sensor
+/// then failing to calibrate or returning corrupted data.
+pub const A121_SPI_MODE: Mode = MODE_0;
+
+/// Highest SPI clock frequency in Hz supported by the sensor.
+///
+/// The achievable frequency is often lower, limited by the board layout and the MCU.
+pub const MAX_SPI_HZ: u32 = 50_000_000;
 
-/// Global instance of a Mutex, wrapping a RefCell that optionally contains a mutable reference to a `SpiBus`.
+/// Smallest maximum SPI transfer size, in bytes, accepted by the radar SDK.
+pub const MIN_SPI_TRANSFER_SIZE: u16 = ACC_HAL_SPI_TRANSFER_SIZE_REQUIRED as u16;
+
+/// Global instance of a Mutex, wrapping a RefCell that contains a mutable reference to the `SpiBus` of each sensor.
 ///
-/// `SPI_INSTANCE` is used to store and provide controlled access to the SPI device required by the radar sensor.
+/// `SPI_INSTANCE` is used to store and provide controlled access to the SPI devices required by the radar sensors.
 /// The `Mutex` ensures thread-safe access in environments where multi-threading is possible, while the `RefCell`
 /// allows for mutable access to the SPI device. This setup is crucial for enabling SPI communications in a safe
 /// and controlled manner within the radar sensor's hardware abstraction layer.
@@ -23,29 +48,165 @@ pub type RefRadarSpi = &'static mut RadarSpi;
 /// The access to the `SPI_INSTANCE` is controlled via a mutex to prevent concurrent access issues.
 /// However, care must be taken to ensure that the SPI device is properly initialized before use
 /// and is not accessed after it has been freed or gone out of scope.
-static SPI_INSTANCE: Mutex<CriticalSectionRawMutex, RefCell<Option<RefRadarSpi>>> =
+///
+/// # Interrupts
+///
+/// Locking a `CriticalSectionRawMutex` enters a critical section, so every SDK transfer runs
+/// with interrupts masked from start to end and cannot be interrupted by other handlers. Keep
+/// this in mind for the interrupt latency of the application, a transfer lasts as long as the
+/// largest chunk the SDK requests, see [`AccHalImpl::set_max_spi_transfer_size`].
+static SPI_INSTANCE: Mutex<CriticalSectionRawMutex, RefCell<SpiMap<RadarSpi>>> =
+    Mutex::new(RefCell::new(SpiMap::new()));
+
+/// Optional 16-bit SPI devices, used for the transfers the SDK makes in 16-bit words.
+///
+/// Access follows the same rules as [`SPI_INSTANCE`].
+static SPI16_INSTANCE: Mutex<CriticalSectionRawMutex, RefCell<SpiMap<RadarSpi16>>> =
+    Mutex::new(RefCell::new(SpiMap::new()));
+
+/// Maximum number of sensors, each with its own SPI device, the HAL can drive.
+pub const MAX_SENSORS: usize = 4;
+
+/// SPI devices of the sensors, looked up by sensor ID in the transfer functions.
+struct SpiMap<T: ?Sized + 'static> {
+    slots: [Option<(acc_sensor_id_t, &'static mut T)>; MAX_SENSORS],
+}
+
+impl<T: ?Sized + 'static> SpiMap<T> {
+    const fn new() -> Self {
+        Self {
+            slots: [None, None, None, None],
+        }
+    }
+
+    /// Sets the device of `sensor_id`, replacing the previous one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if devices are already set for [`MAX_SENSORS`] other sensors.
+    fn insert(&mut self, sensor_id: acc_sensor_id_t, spi: &'static mut T) {
+        let index = self
+            .slots
+            .iter()
+            .position(|slot| matches!(slot, Some((id, _)) if *id == sensor_id))
+            .or_else(|| self.slots.iter().position(Option::is_none))
+            .expect("Too many sensors registered with the HAL");
+        self.slots[index] = Some((sensor_id, spi));
+    }
+
+    #[cfg(test)]
+    fn clear(&mut self) {
+        self.slots = [None, None, None, None];
+    }
+
+    fn get_mut(&mut self, sensor_id: acc_sensor_id_t) -> Option<&mut T> {
+        self.slots.iter_mut().find_map(|slot| match slot {
+            Some((id, spi)) if *id == sensor_id => Some(&mut **spi),
+            _ => None,
+        })
+    }
+}
+
+/// First SPI error since it was last taken, see [`take_spi_error`].
+static SPI_ERROR: Mutex<CriticalSectionRawMutex, Cell<Option<SpiErrorKind>>> =
+    Mutex::new(Cell::new(None));
+
+/// Returns the first SPI transfer error since the last call and clears it.
+///
+/// The SDK transfer callbacks cannot report a failure, a failed transfer is therefore recorded
+/// here and checked by [`Radar`](crate::radar::Radar) after its SDK calls, which then fail with
+/// `SensorError::SpiTransfer`.
+pub fn take_spi_error() -> Option<SpiErrorKind> {
+    SPI_ERROR.lock(|cell| cell.take())
+}
+
+fn record_spi_error(error: SpiErrorKind) {
+    SPI_ERROR.lock(|cell| {
+        if cell.get().is_none() {
+            cell.set(Some(error));
+        }
+    });
+}
+
+/// Longest SDK error message kept by [`take_last_sdk_error`], in bytes.
+pub const MAX_SDK_ERROR_LEN: usize = 128;
+
+/// Error message logged by the radar SDK, see [`take_last_sdk_error`].
+pub type SdkErrorMessage = heapless::String<MAX_SDK_ERROR_LEN>;
+
+/// Last error message logged by the radar SDK, see [`take_last_sdk_error`].
+static LAST_SDK_ERROR: Mutex<CriticalSectionRawMutex, RefCell<Option<SdkErrorMessage>>> =
     Mutex::new(RefCell::new(None));
 
+/// Returns the last error message logged by the radar SDK and clears it.
+///
+/// The SDK has no error codes, the reason of a failed call is only reported through its log.
+/// Call this after a failed operation to get that reason, clearing it makes sure that a message
+/// from an earlier failure is not reported again. The message is kept without allocating, the
+/// part after the first [`MAX_SDK_ERROR_LEN`] bytes is dropped.
+pub fn take_last_sdk_error() -> Option<SdkErrorMessage> {
+    LAST_SDK_ERROR.lock(|cell| cell.borrow_mut().take())
+}
+
+fn record_sdk_error(message: &str) {
+    let mut len = message.len().min(MAX_SDK_ERROR_LEN);
+    while !message.is_char_boundary(len) {
+        len -= 1;
+    }
+    let mut kept = SdkErrorMessage::new();
+    // Fits, `len` is at most the capacity
+    let _ = kept.push_str(&message[..len]);
+    LAST_SDK_ERROR.lock(|cell| cell.replace(Some(kept)));
+}
+
+/// Removes every SPI device, clears the latched errors, the memory statistics and the arena, see
+/// [`HalState`](crate::test_support::HalState).
+#[cfg(test)]
+pub(crate) fn clear_state() {
+    SPI_INSTANCE.lock(|cell| cell.borrow_mut().clear());
+    SPI16_INSTANCE.lock(|cell| cell.borrow_mut().clear());
+    take_spi_error();
+    take_last_sdk_error();
+    #[cfg(feature = "hal-stats")]
+    MEMORY_STATS.lock(|cell| cell.take());
+    #[cfg(feature = "static-alloc")]
+    ARENA.lock(|cell| {
+        cell.set(Arena {
+            start: 0,
+            len: 0,
+            used: 0,
+            live: 0,
+        })
+    });
+}
+
 /// Represents the hardware abstraction layer implementation for the radar sensor.
 ///
 /// This struct encapsulates the necessary functionality to interface with the radar sensor
 /// using the SPI communication protocol and provides methods for memory management and logging.
 pub struct AccHalImpl {
     inner: acc_hal_a121_t,
+    sensor_id: acc_sensor_id_t,
 }
 
 impl AccHalImpl {
     /// Constructs a new `AccHalImpl` instance, registering the SPI device and initializing
     /// the radar hardware abstraction layer.
     ///
+    /// Several sensors can be driven, each with its own SPI device, by creating one `AccHalImpl`
+    /// per sensor ID. The transfer functions select the device from the sensor ID the SDK passes
+    /// to them.
+    ///
     /// # Arguments
     ///
+    /// * `sensor_id` - The ID of the sensor the SPI device is connected to.
     /// * `spi` - A reference to an SPI device that implements the `SpiBus` trait.
     ///
     /// # Panics
     ///
-    /// Panics if the HAL registration fails.
-    pub fn new<SPI>(spi: &'static mut SPI) -> Self
+    /// Panics if the HAL registration fails, or if devices are already set for [`MAX_SENSORS`]
+    /// other sensors.
+    pub fn new<SPI>(sensor_id: u32, spi: &'static mut SPI) -> Self
     where
         SPI: SpiDevice<u8, Error = SpiErrorKind> + Send + 'static,
     {
@@ -58,53 +219,112 @@ impl AccHalImpl {
             log: Some(logger),
             #[cfg(not(feature = "nightly-logger"))]
             log: Some(a121_sys::c_log_stub),
-            optimization: acc_hal_optimization_t { transfer16: None },
+            optimization: acc_hal_optimization_t {
+                transfer16: Some(Self::transfer16_function),
+            },
         };
-        SPI_INSTANCE.lock(|cell| cell.replace(Some(spi)));
-        Self { inner }
+        let sensor_id = sensor_id as acc_sensor_id_t;
+        SPI_INSTANCE.lock(|cell| cell.borrow_mut().insert(sensor_id, spi));
+        Self { inner, sensor_id }
+    }
+
+    /// Adds a 16-bit SPI device to the HAL.
+    ///
+    /// The SDK makes its larger transfers in 16-bit words, which go through `spi16` once it is
+    /// set; many SPI peripherals carry them out faster than the same transfer in bytes. Without
+    /// it the words are sent most significant byte first through the 8-bit device. Both devices
+    /// must address the same sensor, for instance two devices sharing one bus. Sensors without a
+    /// 16-bit device keep using their 8-bit one when other sensors have one.
+    pub fn with_spi16<SPI16>(self, spi16: &'static mut SPI16) -> Self
+    where
+        SPI16: SpiDevice<u16, Error = SpiErrorKind> + Send + 'static,
+    {
+        SPI16_INSTANCE.lock(|cell| cell.borrow_mut().insert(self.sensor_id, spi16));
+        self
+    }
+
+    /// Sets the largest SPI transfer, in bytes, the SDK may request in one call.
+    ///
+    /// The SDK splits larger reads into several transfers of at most this size, and the transfer
+    /// functions carry out each of them on its own, so this is the only setting needed for SPI
+    /// peripherals or DMA channels with a limited transfer length. Values below
+    /// [`MIN_SPI_TRANSFER_SIZE`] are raised to it. Takes effect on the next [`register`].
+    ///
+    /// [`register`]: AccHalImpl::register
+    pub fn set_max_spi_transfer_size(&mut self, max_spi_transfer_size: u16) {
+        self.inner.max_spi_transfer_size = max_spi_transfer_size.max(MIN_SPI_TRANSFER_SIZE);
+    }
+
+    /// Returns the largest SPI transfer, in bytes, the SDK may request in one call.
+    pub fn max_spi_transfer_size(&self) -> u16 {
+        self.inner.max_spi_transfer_size
     }
 
     /// Transfer function for 16-bit data used by the radar SDK.
     ///
-    /// This function is registered as part of the HAL and is called by the radar SDK to
-    /// perform SPI transfers.
+    /// This function is always registered as part of the HAL, the SDK uses it instead of the
+    /// 8-bit transfer function where possible. The words go through the 16-bit SPI device of the
+    /// sensor given with [`AccHalImpl::with_spi16`], or are split into bytes for its 8-bit device.
     ///
     /// # Safety
     ///
     /// This function is unsafe as it involves raw pointers and direct hardware access.
-    #[allow(dead_code)]
     extern "C" fn transfer16_function(
-        _sensor_id: acc_sensor_id_t,
+        sensor_id: acc_sensor_id_t,
         buffer: *mut u16,
         buffer_length: usize,
     ) {
         let tmp_buf = unsafe { core::slice::from_raw_parts_mut(buffer, buffer_length) };
-        trace!(
-            "Transfer16 function called: buffer={:#X} (size:{})",
-            tmp_buf,
-            buffer_length
-        );
-        // Borrow a mutable reference to the SpiBus
-        SPI_INSTANCE.lock(|cell| unsafe {
+        trace!("Transfer16 function called: size:{}", buffer_length);
+        // Borrow a mutable reference to the 16-bit SpiDevice of the sensor
+        let transferred = SPI16_INSTANCE.lock(|cell| {
             let mut binding = cell.borrow_mut();
-            let _spi = binding.as_mut().unwrap_unchecked();
-            // Perform the SPI transfer
-            todo!("Perform the SPI 16 transfer");
+            let Some(spi) = binding.get_mut(sensor_id) else {
+                return false;
+            };
+            // Perform the SPI transfer, the SDK cannot be told about a failure so it is latched
+            if let Err(e) = spi.transfer_in_place(tmp_buf) {
+                record_spi_error(e);
+            }
+            true
         });
+        if !transferred {
+            // No 16-bit device for this sensor, send the words most significant byte first as
+            // they appear on the wire
+            for word in tmp_buf.iter_mut() {
+                *word = word.to_be();
+            }
+            let byte_buf = unsafe {
+                core::slice::from_raw_parts_mut(tmp_buf.as_mut_ptr() as *mut u8, buffer_length * 2)
+            };
+            Self::transfer8(sensor_id, byte_buf);
+            for word in tmp_buf.iter_mut() {
+                *word = u16::from_be(*word);
+            }
+        }
     }
 
     extern "C" fn transfer8_function(
-        _sensor_id: acc_sensor_id_t,
+        sensor_id: acc_sensor_id_t,
         buffer: *mut u8,
         buffer_length: usize,
     ) {
         let tmp_buf = unsafe { core::slice::from_raw_parts_mut(buffer, buffer_length) };
-        // Borrow a mutable reference to the SpiBus
-        SPI_INSTANCE.lock(|cell| unsafe {
+        Self::transfer8(sensor_id, tmp_buf);
+    }
+
+    fn transfer8(sensor_id: acc_sensor_id_t, buffer: &mut [u8]) {
+        // Borrow a mutable reference to the SpiDevice of the sensor
+        SPI_INSTANCE.lock(|cell| {
             let mut binding = cell.borrow_mut();
-            let spi = binding.as_mut().unwrap_unchecked();
-            // Perform the SPI transfer
-            spi.transfer_in_place(tmp_buf).unwrap_unchecked();
+            let Some(spi) = binding.get_mut(sensor_id) else {
+                record_spi_error(SpiErrorKind::Other);
+                return;
+            };
+            // Perform the SPI transfer, the SDK cannot be told about a failure so it is latched
+            if let Err(e) = spi.transfer_in_place(buffer) {
+                record_spi_error(e);
+            }
         });
     }
 
@@ -122,8 +342,43 @@ impl AccHalImpl {
         let result = unsafe { acc_rss_hal_register(&self.inner) };
         assert!(result, "Failed to register HAL");
     }
+
+    /// Returns the memory allocated by the radar SDK so far.
+    ///
+    /// The peak is the heap the SDK needs for the configurations used since startup, sensor and
+    /// detector handles included. Counters are shared by all sensors.
+    #[cfg(feature = "hal-stats")]
+    pub fn memory_stats() -> HalMemoryStats {
+        MEMORY_STATS.lock(Cell::get)
+    }
+
+    /// Sets the memory the radar SDK allocates from.
+    ///
+    /// With the `static-alloc` feature the SDK never calls `malloc`, its allocations are carved
+    /// out of `arena` instead. Call this before creating the radar; the buffer sizes reported by
+    /// the sensor and detectors give an idea of the size needed, the `hal-stats` feature reports
+    /// the peak usage. An allocation that does not fit in the arena fails, and the SDK call that
+    /// needed it returns an error. Memory is reused like a stack: it is reclaimed when the latest
+    /// allocation is freed or when everything has been freed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if memory from a previously set arena is still allocated.
+    #[cfg(feature = "static-alloc")]
+    pub fn set_arena(arena: &'static mut [u8]) {
+        ARENA.lock(|cell| {
+            assert_eq!(cell.get().live, 0, "Radar SDK memory still allocated");
+            cell.set(Arena {
+                start: arena.as_mut_ptr() as usize,
+                len: arena.len(),
+                used: 0,
+                live: 0,
+            });
+        });
+    }
 }
 
+#[cfg(not(any(feature = "rust-alloc", feature = "static-alloc")))]
 extern "C" {
     fn malloc(size: usize) -> *mut c_void;
     fn free(ptr: *mut c_void);
@@ -134,6 +389,11 @@ extern "C" {
 /// # Safety
 ///
 /// This function is unsafe as it performs raw pointer manipulation.
+#[cfg(not(any(
+    feature = "rust-alloc",
+    feature = "hal-stats",
+    feature = "static-alloc"
+)))]
 unsafe extern "C" fn mem_alloc(size: usize) -> *mut c_void {
     malloc(size)
 }
@@ -143,22 +403,235 @@ unsafe extern "C" fn mem_alloc(size: usize) -> *mut c_void {
 /// # Safety
 ///
 /// This function is unsafe as it performs raw pointer manipulation.
+#[cfg(not(any(
+    feature = "rust-alloc",
+    feature = "hal-stats",
+    feature = "static-alloc"
+)))]
 unsafe extern "C" fn mem_free(ptr: *mut c_void) {
     free(ptr);
 }
 
+/// Alignment of the allocations made for the radar SDK, the alignment `malloc` guarantees on the
+/// supported targets.
+#[cfg(any(
+    feature = "rust-alloc",
+    feature = "hal-stats",
+    feature = "static-alloc"
+))]
+const ALLOC_ALIGN: usize = 8;
+
+/// Size of the header in front of each allocation, holding the size of the allocation.
+///
+/// As large as the alignment so that the memory handed to the SDK stays aligned.
+#[cfg(any(
+    feature = "rust-alloc",
+    feature = "hal-stats",
+    feature = "static-alloc"
+))]
+const ALLOC_HEADER: usize = ALLOC_ALIGN;
+
+/// Allocates memory for use by the radar SDK.
+///
+/// The size is stored in a header in front of the returned memory, for [`mem_free`] to know the
+/// size of the allocation.
+///
+/// # Safety
+///
+/// This function is unsafe as it performs raw pointer manipulation.
+#[cfg(any(
+    feature = "rust-alloc",
+    feature = "hal-stats",
+    feature = "static-alloc"
+))]
+unsafe extern "C" fn mem_alloc(size: usize) -> *mut c_void {
+    let Some(total) = size.checked_add(ALLOC_HEADER) else {
+        return core::ptr::null_mut();
+    };
+    let ptr = raw_alloc(total);
+    if ptr.is_null() {
+        return core::ptr::null_mut();
+    }
+    ptr.cast::<usize>().write(total);
+    #[cfg(feature = "hal-stats")]
+    record_alloc(size);
+    ptr.add(ALLOC_HEADER).cast()
+}
+
+/// Frees memory previously allocated for the radar SDK by [`mem_alloc`].
+///
+/// # Safety
+///
+/// This function is unsafe as it performs raw pointer manipulation.
+#[cfg(any(
+    feature = "rust-alloc",
+    feature = "hal-stats",
+    feature = "static-alloc"
+))]
+unsafe extern "C" fn mem_free(ptr: *mut c_void) {
+    if ptr.is_null() {
+        return;
+    }
+    let ptr = ptr.cast::<u8>().sub(ALLOC_HEADER);
+    let total = ptr.cast::<usize>().read();
+    #[cfg(feature = "hal-stats")]
+    record_free(total - ALLOC_HEADER);
+    raw_free(ptr, total);
+}
+
+/// Allocates `size` bytes aligned to [`ALLOC_ALIGN`] from the global allocator.
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+unsafe fn raw_alloc(size: usize) -> *mut u8 {
+    match core::alloc::Layout::from_size_align(size, ALLOC_ALIGN) {
+        Ok(layout) => alloc::alloc::alloc(layout),
+        Err(_) => core::ptr::null_mut(),
+    }
+}
+
+/// Frees `size` bytes previously allocated by [`raw_alloc`].
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+unsafe fn raw_free(ptr: *mut u8, size: usize) {
+    let layout = core::alloc::Layout::from_size_align_unchecked(size, ALLOC_ALIGN);
+    alloc::alloc::dealloc(ptr, layout);
+}
+
+/// Allocates `size` bytes with `malloc`.
+#[cfg(all(
+    feature = "hal-stats",
+    not(any(feature = "rust-alloc", feature = "static-alloc"))
+))]
+unsafe fn raw_alloc(size: usize) -> *mut u8 {
+    malloc(size).cast()
+}
+
+/// Frees memory previously allocated by [`raw_alloc`].
+#[cfg(all(
+    feature = "hal-stats",
+    not(any(feature = "rust-alloc", feature = "static-alloc"))
+))]
+unsafe fn raw_free(ptr: *mut u8, _size: usize) {
+    free(ptr.cast());
+}
+
+/// Arena the radar SDK allocates from, see [`AccHalImpl::set_arena`].
+///
+/// Memory is handed out from the start of the arena upwards. Freeing the most recent allocation
+/// gives its memory back, all other memory is only reclaimed once every allocation is freed.
+/// Addresses are kept as `usize`, a raw pointer would keep the mutex from being `Sync`.
+#[cfg(feature = "static-alloc")]
+#[derive(Copy, Clone)]
+struct Arena {
+    start: usize,
+    len: usize,
+    used: usize,
+    live: usize,
+}
+
+#[cfg(feature = "static-alloc")]
+static ARENA: Mutex<CriticalSectionRawMutex, Cell<Arena>> = Mutex::new(Cell::new(Arena {
+    start: 0,
+    len: 0,
+    used: 0,
+    live: 0,
+}));
+
+/// Allocates `size` bytes aligned to [`ALLOC_ALIGN`] from the arena.
+///
+/// Returns a null pointer if no arena is set or the arena is exhausted, which the SDK reports
+/// as a failed allocation.
+#[cfg(feature = "static-alloc")]
+unsafe fn raw_alloc(size: usize) -> *mut u8 {
+    ARENA.lock(|cell| {
+        let mut arena = cell.get();
+        let top = arena.start + arena.used;
+        let padding = (ALLOC_ALIGN - top % ALLOC_ALIGN) % ALLOC_ALIGN;
+        let Some(used) = arena
+            .used
+            .checked_add(padding)
+            .and_then(|used| used.checked_add(size))
+            .filter(|&used| used <= arena.len)
+        else {
+            return core::ptr::null_mut();
+        };
+        arena.used = used;
+        arena.live += 1;
+        cell.set(arena);
+        (top + padding) as *mut u8
+    })
+}
+
+/// Frees `size` bytes previously allocated by [`raw_alloc`].
+#[cfg(feature = "static-alloc")]
+unsafe fn raw_free(ptr: *mut u8, size: usize) {
+    ARENA.lock(|cell| {
+        let mut arena = cell.get();
+        let offset = ptr as usize - arena.start;
+        arena.live -= 1;
+        if arena.live == 0 {
+            arena.used = 0;
+        } else if offset + size == arena.used {
+            arena.used = offset;
+        }
+        cell.set(arena);
+    });
+}
+
+/// Memory allocated by the radar SDK, see [`AccHalImpl::memory_stats`].
+#[cfg(feature = "hal-stats")]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, defmt::Format)]
+pub struct HalMemoryStats {
+    /// Bytes currently allocated.
+    pub current: usize,
+    /// Highest number of bytes allocated at the same time.
+    pub peak: usize,
+    /// Number of allocations made.
+    pub alloc_count: u32,
+}
+
+#[cfg(feature = "hal-stats")]
+static MEMORY_STATS: Mutex<CriticalSectionRawMutex, Cell<HalMemoryStats>> =
+    Mutex::new(Cell::new(HalMemoryStats {
+        current: 0,
+        peak: 0,
+        alloc_count: 0,
+    }));
+
+#[cfg(feature = "hal-stats")]
+fn record_alloc(size: usize) {
+    MEMORY_STATS.lock(|cell| {
+        let mut stats = cell.get();
+        stats.current += size;
+        stats.peak = stats.peak.max(stats.current);
+        stats.alloc_count = stats.alloc_count.wrapping_add(1);
+        cell.set(stats);
+    });
+}
+
+#[cfg(feature = "hal-stats")]
+fn record_free(size: usize) {
+    MEMORY_STATS.lock(|cell| {
+        let mut stats = cell.get();
+        stats.current = stats.current.saturating_sub(size);
+        cell.set(stats);
+    });
+}
+
 #[cfg(feature = "nightly-logger")]
 unsafe extern "C" fn logger(
     level: a121_sys::acc_log_level_t,
     module: *const c_char,
     format: *const c_char,
-    mut _va: ...
+    mut va: ...
 ) {
     let module = unsafe { CStr::from_ptr(module) };
     let format = unsafe { CStr::from_ptr(format) };
-    let message = format.to_str().unwrap_or("");
+    let message = unsafe { format_message(format.to_bytes(), &mut va) };
+    let message = message.as_str();
     match level {
-        0 => defmt::error!("{}: {}", module.to_str().unwrap_or(""), message),
+        0 => {
+            record_sdk_error(message);
+            defmt::error!("{}: {}", module.to_str().unwrap_or(""), message)
+        }
         1 => defmt::warn!("{}: {}", module.to_str().unwrap_or(""), message),
         2 => defmt::info!("{}: {}", module.to_str().unwrap_or(""), message),
         3 => defmt::debug!("{}: {}", module.to_str().unwrap_or(""), message),
@@ -167,6 +640,175 @@ unsafe extern "C" fn logger(
     }
 }
 
+/// Formats a printf style message of the SDK with its arguments.
+///
+/// Supports the `d`, `i`, `u`, `x`, `X`, `c`, `s`, `f`, `p` and `%` conversions with the `-` and
+/// `0` flags, a width and a precision given as digits or as `*` arguments, and the `h`, `hh`,
+/// `l`, `ll` and `z` length modifiers. An unsupported conversion is written as is, without
+/// reading an argument for it.
+///
+/// # Safety
+///
+/// The arguments in `va` must match the conversions in `format`.
+#[cfg(feature = "nightly-logger")]
+// `c_long` and `c_ulong` are 32 bits wide on the embedded targets
+#[allow(clippy::useless_conversion)]
+unsafe fn format_message(format: &[u8], va: &mut core::ffi::VaList) -> String {
+    use core::ffi::{c_int, c_long, c_longlong, c_uint, c_ulong, c_ulonglong};
+
+    /// Size of an integer argument, from the length modifier of its conversion.
+    #[derive(Copy, Clone)]
+    enum Length {
+        Int,
+        Long,
+        LongLong,
+        Size,
+    }
+    use core::fmt::Write;
+
+    let mut out = String::new();
+    let mut arg = String::new();
+    let mut bytes = format.iter().copied().peekable();
+    while let Some(b) = bytes.next() {
+        if b != b'%' {
+            out.push(b as char);
+            continue;
+        }
+
+        let mut left = false;
+        let mut zero = false;
+        while let Some(&flag @ (b'-' | b'0' | b'+' | b' ' | b'#')) = bytes.peek() {
+            left |= flag == b'-';
+            zero |= flag == b'0';
+            bytes.next();
+        }
+        let mut width = 0;
+        if bytes.peek() == Some(&b'*') {
+            bytes.next();
+            // A negative width argument is a `-` flag followed by a width
+            let value = unsafe { va.next_arg::<c_int>() };
+            left |= value < 0;
+            width = value.unsigned_abs() as usize;
+        }
+        while let Some(&digit @ b'0'..=b'9') = bytes.peek() {
+            width = width * 10 + usize::from(digit - b'0');
+            bytes.next();
+        }
+        let mut precision = None;
+        if bytes.peek() == Some(&b'.') {
+            bytes.next();
+            if bytes.peek() == Some(&b'*') {
+                bytes.next();
+                // A negative precision argument is taken as if the precision were omitted
+                precision = usize::try_from(unsafe { va.next_arg::<c_int>() }).ok();
+            } else {
+                let mut p = 0;
+                while let Some(&digit @ b'0'..=b'9') = bytes.peek() {
+                    p = p * 10 + usize::from(digit - b'0');
+                    bytes.next();
+                }
+                precision = Some(p);
+            }
+        }
+        let mut length = Length::Int;
+        while let Some(&modifier @ (b'h' | b'l' | b'z')) = bytes.peek() {
+            length = match (modifier, length) {
+                (b'l', Length::Long) => Length::LongLong,
+                (b'l', _) => Length::Long,
+                (b'z', _) => Length::Size,
+                // `short` and `char` arguments are promoted to `int`
+                _ => length,
+            };
+            bytes.next();
+        }
+
+        arg.clear();
+        let numeric = match bytes.next() {
+            Some(b'd' | b'i') => {
+                let value = match length {
+                    Length::Int => i64::from(unsafe { va.next_arg::<c_int>() }),
+                    Length::Long => i64::from(unsafe { va.next_arg::<c_long>() }),
+                    Length::LongLong => unsafe { va.next_arg::<c_longlong>() },
+                    Length::Size => unsafe { va.next_arg::<isize>() as i64 },
+                };
+                let _ = write!(arg, "{}", value);
+                true
+            }
+            Some(conversion @ (b'u' | b'x' | b'X')) => {
+                let value = match length {
+                    Length::Int => u64::from(unsafe { va.next_arg::<c_uint>() }),
+                    Length::Long => u64::from(unsafe { va.next_arg::<c_ulong>() }),
+                    Length::LongLong => unsafe { va.next_arg::<c_ulonglong>() },
+                    Length::Size => unsafe { va.next_arg::<usize>() as u64 },
+                };
+                let _ = match conversion {
+                    b'u' => write!(arg, "{}", value),
+                    b'x' => write!(arg, "{:x}", value),
+                    _ => write!(arg, "{:X}", value),
+                };
+                true
+            }
+            Some(b'f') => {
+                let value = unsafe { va.next_arg::<f64>() };
+                let _ = write!(arg, "{:.*}", precision.unwrap_or(6), value);
+                true
+            }
+            Some(b'c') => {
+                arg.push(unsafe { va.next_arg::<c_int>() } as u8 as char);
+                false
+            }
+            Some(b's') => {
+                let ptr = unsafe { va.next_arg::<*const c_char>() };
+                if ptr.is_null() {
+                    arg.push_str("(null)");
+                } else {
+                    let s = unsafe { CStr::from_ptr(ptr) }.to_str().unwrap_or("");
+                    match precision {
+                        Some(p) => arg.extend(s.chars().take(p)),
+                        None => arg.push_str(s),
+                    }
+                }
+                false
+            }
+            Some(b'p') => {
+                let _ = write!(arg, "{:p}", unsafe { va.next_arg::<*const c_void>() });
+                false
+            }
+            Some(b'%') => {
+                out.push('%');
+                continue;
+            }
+            Some(other) => {
+                out.push('%');
+                out.push(other as char);
+                continue;
+            }
+            None => {
+                out.push('%');
+                break;
+            }
+        };
+
+        let pad = width.saturating_sub(arg.chars().count());
+        if left {
+            out.push_str(&arg);
+            out.extend(core::iter::repeat(' ').take(pad));
+        } else if zero && numeric {
+            let (sign, digits) = match arg.strip_prefix('-') {
+                Some(digits) => ("-", digits),
+                None => ("", arg.as_str()),
+            };
+            out.push_str(sign);
+            out.extend(core::iter::repeat('0').take(pad));
+            out.push_str(digits);
+        } else {
+            out.extend(core::iter::repeat(' ').take(pad));
+            out.push_str(&arg);
+        }
+    }
+    out
+}
+
 #[cfg(not(feature = "nightly-logger"))]
 /// This function is called by the C stub to log messages from the SDK.
 /// # Safety
@@ -177,7 +819,10 @@ pub unsafe extern "C" fn rust_log(level: u32, message: *const c_char) {
     let str_slice = c_str.to_str().unwrap_or("");
 
     match level {
-        0 => defmt::error!("{}", str_slice),
+        0 => {
+            record_sdk_error(str_slice);
+            defmt::error!("{}", str_slice)
+        }
         1 => defmt::warn!("{}", str_slice),
         2 => defmt::info!("{}", str_slice),
         3 => defmt::debug!("{}", str_slice),
@@ -185,3 +830,272 @@ pub unsafe extern "C" fn rust_log(level: u32, message: *const c_char) {
         _ => defmt::error!("Unknown log level: {}", level),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+    use crate::test_support::allocated_bytes;
+    use crate::test_support::{FakeSpi, HalState};
+    #[cfg(any(feature = "nightly-logger", feature = "static-alloc"))]
+    use std::boxed::Box;
+
+    #[cfg(feature = "nightly-logger")]
+    unsafe extern "C" fn format(format: *const c_char, mut va: ...) -> *mut String {
+        let format = CStr::from_ptr(format).to_bytes();
+        Box::into_raw(Box::new(format_message(format, &mut va)))
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    fn take(message: *mut String) -> String {
+        *unsafe { Box::from_raw(message) }
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_reads_long_long_arguments() {
+        use core::ffi::{c_int, c_longlong, c_ulonglong};
+        let message = unsafe {
+            take(format(
+                b"%lld %llx %d\0".as_ptr().cast(),
+                -5_000_000_000 as c_longlong,
+                0x1_0000_0000 as c_ulonglong,
+                7 as c_int,
+            ))
+        };
+        assert_eq!(message, "-5000000000 100000000 7");
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_reads_star_width_and_precision() {
+        use core::ffi::c_int;
+        let message = unsafe {
+            take(format(
+                b"[%*d] [%-*d] [%.*f] %d\0".as_ptr().cast(),
+                4 as c_int,
+                1 as c_int,
+                -3 as c_int,
+                2 as c_int,
+                1 as c_int,
+                1.25f64,
+                9 as c_int,
+            ))
+        };
+        assert_eq!(message, "[   1] [2  ] [1.2] 9");
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_formats_signed_integers() {
+        use core::ffi::c_int;
+        let message = unsafe {
+            take(format(
+                b"%d %i %05d\0".as_ptr().cast(),
+                -42 as c_int,
+                0 as c_int,
+                -7 as c_int,
+            ))
+        };
+        assert_eq!(message, "-42 0 -0007");
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_formats_unsigned_integers() {
+        use core::ffi::c_uint;
+        let message = unsafe {
+            take(format(
+                b"%u %4u\0".as_ptr().cast(),
+                c_uint::MAX,
+                7 as c_uint,
+            ))
+        };
+        assert_eq!(message, "4294967295    7");
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_formats_lowercase_hex() {
+        use core::ffi::c_uint;
+        let message = unsafe {
+            take(format(
+                b"%x %08x\0".as_ptr().cast(),
+                0xbeef as c_uint,
+                0x2a as c_uint,
+            ))
+        };
+        assert_eq!(message, "beef 0000002a");
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_formats_uppercase_hex() {
+        use core::ffi::c_uint;
+        let message = unsafe {
+            take(format(
+                b"%X %04X\0".as_ptr().cast(),
+                0xbeef as c_uint,
+                0x2a as c_uint,
+            ))
+        };
+        assert_eq!(message, "BEEF 002A");
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_formats_strings() {
+        let message = unsafe {
+            take(format(
+                b"%s [%.3s] [%-6s] %s\0".as_ptr().cast(),
+                b"sensor\0".as_ptr().cast::<c_char>(),
+                b"calibration\0".as_ptr().cast::<c_char>(),
+                b"ok\0".as_ptr().cast::<c_char>(),
+                core::ptr::null::<c_char>(),
+            ))
+        };
+        assert_eq!(message, "sensor [cal] [ok    ] (null)");
+    }
+
+    #[cfg(feature = "nightly-logger")]
+    #[test]
+    fn format_message_formats_floats() {
+        let message = unsafe {
+            take(format(
+                b"%f %.2f %8.1f\0".as_ptr().cast(),
+                1.5f64,
+                -0.125f64,
+                3.25f64,
+            ))
+        };
+        assert_eq!(message, "1.500000 -0.12      3.2");
+    }
+
+    #[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+    #[test]
+    fn mem_alloc_uses_the_global_allocator() {
+        let _hal = HalState::lock();
+        for size in [1, 7, 8, 100, 4096] {
+            let before = allocated_bytes();
+            let ptr = unsafe { mem_alloc(size) };
+            assert!(!ptr.is_null());
+            assert_eq!(ptr as usize % ALLOC_ALIGN, 0);
+            assert_eq!(allocated_bytes().wrapping_sub(before), size + ALLOC_HEADER);
+            unsafe {
+                core::ptr::write_bytes(ptr.cast::<u8>(), 0xa5, size);
+                mem_free(ptr);
+            }
+            assert_eq!(allocated_bytes(), before);
+        }
+    }
+
+    #[cfg(feature = "hal-stats")]
+    #[test]
+    fn memory_stats_track_the_peak() {
+        let _hal = HalState::lock();
+        #[cfg(feature = "static-alloc")]
+        AccHalImpl::set_arena(Box::leak(std::vec![0; 512].into_boxed_slice()));
+
+        let (a, b) = unsafe { (mem_alloc(100), mem_alloc(50)) };
+        unsafe { mem_free(a) };
+        let c = unsafe { mem_alloc(20) };
+        assert_eq!(
+            AccHalImpl::memory_stats(),
+            HalMemoryStats {
+                current: 70,
+                peak: 150,
+                alloc_count: 3,
+            }
+        );
+
+        unsafe {
+            mem_free(b);
+            mem_free(c);
+        }
+        let stats = AccHalImpl::memory_stats();
+        assert_eq!((stats.current, stats.peak), (0, 150));
+    }
+
+    #[cfg(feature = "static-alloc")]
+    #[test]
+    fn exhausted_arena_fails_allocations_until_freed() {
+        let _hal = HalState::lock();
+        assert!(unsafe { mem_alloc(1) }.is_null(), "no arena set");
+        AccHalImpl::set_arena(Box::leak(Box::new([0; 64])));
+
+        let a = unsafe { mem_alloc(48) };
+        assert!(!a.is_null());
+        assert!(unsafe { mem_alloc(1) }.is_null());
+
+        unsafe { mem_free(a) };
+        let b = unsafe { mem_alloc(48) };
+        assert!(!b.is_null());
+        unsafe { mem_free(b) };
+    }
+
+    #[test]
+    fn last_sdk_error_is_taken_once() {
+        let _hal = HalState::lock();
+        record_sdk_error("first");
+        record_sdk_error("Sensor error 0x2A");
+        assert_eq!(take_last_sdk_error().as_deref(), Some("Sensor error 0x2A"));
+        assert_eq!(take_last_sdk_error(), None);
+    }
+
+    #[test]
+    fn long_sdk_errors_are_truncated_on_a_char_boundary() {
+        let _hal = HalState::lock();
+        let mut message = "x".repeat(MAX_SDK_ERROR_LEN - 1);
+        message.push('é');
+        record_sdk_error(&message);
+        let kept = take_last_sdk_error().unwrap();
+        assert_eq!(kept.as_str(), &message[..MAX_SDK_ERROR_LEN - 1]);
+    }
+
+    #[test]
+    fn transfer16_splits_words_most_significant_byte_first() {
+        let _hal = HalState::lock();
+        let (spi, written) = FakeSpi::<u8>::leaked(&[0x01, 0x02, 0x03, 0x04]);
+        AccHalImpl::new(0, spi);
+
+        let mut words = [0x1234u16, 0xabcd];
+        AccHalImpl::transfer16_function(0, words.as_mut_ptr(), words.len());
+
+        assert_eq!(*written.lock().unwrap(), [0x12, 0x34, 0xab, 0xcd]);
+        assert_eq!(words, [0x0102, 0x0304]);
+    }
+
+    #[test]
+    fn transfer16_uses_the_16_bit_device_when_set() {
+        let _hal = HalState::lock();
+        let (spi, written8) = FakeSpi::<u8>::leaked(&[]);
+        let (spi16, written16) = FakeSpi::<u16>::leaked(&[0x0102, 0x0304]);
+        AccHalImpl::new(0, spi).with_spi16(spi16);
+
+        let mut words = [0x1234u16, 0xabcd];
+        AccHalImpl::transfer16_function(0, words.as_mut_ptr(), words.len());
+
+        assert_eq!(*written16.lock().unwrap(), [0x1234, 0xabcd]);
+        assert!(written8.lock().unwrap().is_empty());
+        assert_eq!(words, [0x0102, 0x0304]);
+    }
+
+    #[test]
+    fn transfer16_keeps_the_16_bit_device_of_other_sensors() {
+        let _hal = HalState::lock();
+        let (spi, _) = FakeSpi::<u8>::leaked(&[]);
+        let (spi16, written16) = FakeSpi::<u16>::leaked(&[0, 0]);
+        AccHalImpl::new(0, spi).with_spi16(spi16);
+        let (spi, written8) = FakeSpi::<u8>::leaked(&[0, 0]);
+        let hal = AccHalImpl::new(1, spi);
+        assert!(hal.inner.optimization.transfer16.is_some());
+
+        for sensor_id in [0, 1] {
+            let mut words = [0x1234u16];
+            AccHalImpl::transfer16_function(sensor_id, words.as_mut_ptr(), words.len());
+        }
+
+        assert_eq!(*written16.lock().unwrap(), [0x1234]);
+        assert_eq!(*written8.lock().unwrap(), [0x12, 0x34]);
+    }
+}
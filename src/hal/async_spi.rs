@@ -0,0 +1,138 @@
+//! Adapter for using an async SPI device with the radar SDK.
+//!
+//! The SDK performs its SPI transfers from synchronous callbacks, it waits for each transfer to
+//! complete before it continues with the next one. A transfer can therefore not be handed over
+//! to the executor, the callback only returns once the transfer is done. [`BlockingAsyncSpi`]
+//! bridges the two by polling the future of the async transfer in a busy loop until it
+//! completes, so a DMA based driver can be used as is instead of through a driver specific
+//! blocking adapter. Other tasks only run between SDK calls, such as while a measurement is
+//! awaited.
+
+use core::future::Future;
+use core::hint::spin_loop;
+use core::pin::pin;
+use core::task::{Context, Poll};
+
+use embedded_hal::spi::{Error, ErrorKind, ErrorType, Operation, SpiDevice};
+use embedded_hal_async::spi::SpiDevice as AsyncSpiDevice;
+
+use crate::radar::noop_waker;
+
+/// Blocking SPI device driving an async SPI device to completion on each transfer.
+///
+/// Errors of the wrapped device are reported by their [`ErrorKind`], as the HAL expects.
+pub struct BlockingAsyncSpi<SPI> {
+    spi: SPI,
+}
+
+impl<SPI> BlockingAsyncSpi<SPI> {
+    /// Wraps `spi`, each transaction then busy-polls the async transaction until it completes.
+    pub fn new(spi: SPI) -> Self {
+        Self { spi }
+    }
+
+    /// Returns the wrapped async SPI device.
+    pub fn into_inner(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI> ErrorType for BlockingAsyncSpi<SPI> {
+    type Error = ErrorKind;
+}
+
+impl<Word, SPI> SpiDevice<Word> for BlockingAsyncSpi<SPI>
+where
+    Word: Copy + 'static,
+    SPI: AsyncSpiDevice<Word>,
+{
+    fn transaction(&mut self, operations: &mut [Operation<'_, Word>]) -> Result<(), Self::Error> {
+        block_on(self.spi.transaction(operations)).map_err(|e| e.kind())
+    }
+}
+
+/// Polls `future` until it completes.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+        spin_loop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Async SPI device with MISO wired to MOSI, each transaction is pending once before it
+    /// completes.
+    struct LoopbackSpi {
+        fail: Option<ErrorKind>,
+    }
+
+    impl ErrorType for LoopbackSpi {
+        type Error = ErrorKind;
+    }
+
+    impl AsyncSpiDevice<u8> for LoopbackSpi {
+        async fn transaction(
+            &mut self,
+            operations: &mut [Operation<'_, u8>],
+        ) -> Result<(), Self::Error> {
+            let mut pending = true;
+            core::future::poll_fn(|cx| {
+                if core::mem::take(&mut pending) {
+                    cx.waker().wake_by_ref();
+                    Poll::Pending
+                } else {
+                    Poll::Ready(())
+                }
+            })
+            .await;
+            if let Some(kind) = self.fail {
+                return Err(kind);
+            }
+            for operation in operations {
+                match operation {
+                    Operation::Transfer(read, write) => {
+                        let len = read.len().min(write.len());
+                        read[..len].copy_from_slice(&write[..len]);
+                    }
+                    // Every word read is the word written at the same time
+                    Operation::TransferInPlace(_) | Operation::Write(_) => {}
+                    Operation::Read(words) => words.fill(0),
+                    Operation::DelayNs(_) => {}
+                }
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn transfers_are_driven_to_completion() {
+        let mut spi = BlockingAsyncSpi::new(LoopbackSpi { fail: None });
+        let mut read = [0u8; 3];
+        let mut in_place = [4u8, 5, 6];
+        spi.transaction(&mut [
+            Operation::Transfer(&mut read, &[1, 2, 3]),
+            Operation::TransferInPlace(&mut in_place),
+        ])
+        .unwrap();
+        assert_eq!(read, [1, 2, 3]);
+        assert_eq!(in_place, [4, 5, 6]);
+    }
+
+    #[test]
+    fn errors_are_reported_by_kind() {
+        let mut spi = BlockingAsyncSpi::new(LoopbackSpi {
+            fail: Some(ErrorKind::ModeFault),
+        });
+        let mut words = [0u8; 2];
+        let result = spi.transaction(&mut [Operation::TransferInPlace(&mut words)]);
+        assert_eq!(result, Err(ErrorKind::ModeFault));
+    }
+}
@@ -27,6 +27,8 @@
 //! to abstract over the specific hardware interfaces used to communicate with the A121 sensor.
 //! This design allows `a121-rs` to be hardware agnostic, supporting a wide range of embedded platforms
 //! by leveraging the embedded-hal ecosystem.
+//! The SPI device given to the radar must use [`hal::A121_SPI_MODE`] and run at most at
+//! [`hal::MAX_SPI_HZ`].
 //! There is still work to be done to make the HAL more flexible and configurable, but the current
 //! implementation is a good starting point for most use cases.
 //!
@@ -57,3 +59,9 @@ pub mod processing;
 pub mod radar;
 /// Sensor module for the radar sensor
 mod sensor;
+pub use sensor::calibration::{CalibrationInfo, CalibrationResult, CalibrationSettings};
+pub use sensor::error::SensorError;
+/// Services of the radar sensor
+pub mod service;
+#[cfg(test)]
+mod test_support;
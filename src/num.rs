@@ -19,6 +19,92 @@ impl AccComplex {
     pub fn ptr(&self) -> *const acc_int16_complex_t {
         &self.inner
     }
+
+    /// Approximate magnitude of the complex value, see [`fast_magnitude`].
+    pub fn fast_magnitude(&self) -> u16 {
+        fast_magnitude(Complex::new(self.inner.real, self.inner.imag))
+    }
+}
+
+/// Approximates the magnitude of an IQ sample using integer arithmetic only.
+///
+/// Uses the alpha max plus beta min approximation with alpha = 15/16 and beta = 15/32, which
+/// stays within 6.25% of the exact magnitude. This avoids the float square root on targets
+/// without an FPU.
+pub fn fast_magnitude(value: Complex<i16>) -> u16 {
+    let re = u32::from(value.re.unsigned_abs());
+    let im = u32::from(value.im.unsigned_abs());
+    let (max, min) = if re > im { (re, im) } else { (im, re) };
+    ((30 * max + 15 * min) / 32) as u16
+}
+
+/// Magnitude of an IQ sample as returned by [`magnitude`], the `u16` of [`fast_magnitude`] with
+/// the `fixed-point` feature.
+#[cfg(feature = "fixed-point")]
+pub type Magnitude = u16;
+
+/// Magnitude of an IQ sample as returned by [`magnitude`], the exact `f32` magnitude without the
+/// `fixed-point` feature.
+#[cfg(not(feature = "fixed-point"))]
+pub type Magnitude = f32;
+
+/// Returns the magnitude of an IQ sample, see [`Magnitude`].
+///
+/// With the `fixed-point` feature this is [`fast_magnitude`], trading up to 6.25% of accuracy
+/// for integer arithmetic only. Otherwise the exact magnitude is computed with the `sqrtf` the
+/// radar SDK links against, see the `libm` feature.
+pub fn magnitude(value: Complex<i16>) -> Magnitude {
+    #[cfg(feature = "fixed-point")]
+    {
+        fast_magnitude(value)
+    }
+    #[cfg(not(feature = "fixed-point"))]
+    {
+        extern "C" {
+            fn sqrtf(f: f32) -> f32;
+        }
+        let (re, im) = (f32::from(value.re), f32::from(value.im));
+        unsafe { sqrtf(re * re + im * im) }
+    }
+}
+
+/// Reinterprets a buffer of interleaved `i16` IQ pairs, real part first, as complex samples
+/// without copying.
+///
+/// Returns `None` if `bytes` is not aligned for `i16` or its length is not a whole number of
+/// samples, use [`copy_iq_samples`] for such buffers.
+pub fn iq_samples(bytes: &[u8]) -> Option<&[Complex<i16>]> {
+    // `Complex<i16>` is `repr(C)` with two `i16` fields, any bit pattern is a valid value
+    let (prefix, samples, suffix) = unsafe { bytes.align_to::<Complex<i16>>() };
+    (prefix.is_empty() && suffix.is_empty()).then_some(samples)
+}
+
+/// Copies a buffer of interleaved `i16` IQ pairs, real part first, into `out`.
+///
+/// Works for any alignment of `bytes`. Returns the number of samples copied, the smaller of the
+/// number of whole samples in `bytes` and the length of `out`.
+pub fn copy_iq_samples(bytes: &[u8], out: &mut [Complex<i16>]) -> usize {
+    let mut count = 0;
+    for (sample, chunk) in out.iter_mut().zip(bytes.chunks_exact(4)) {
+        sample.re = i16::from_ne_bytes([chunk[0], chunk[1]]);
+        sample.im = i16::from_ne_bytes([chunk[2], chunk[3]]);
+        count += 1;
+    }
+    count
+}
+
+/// Converts IQ samples to `Complex<f32>` multiplied by `scale`, for float processing such as an
+/// FFT.
+///
+/// A `scale` of `1.0 / 32768.0` maps the full `i16` range to [-1.0, 1.0). Returns the number of
+/// samples converted, the smaller of the lengths of `samples` and `out`.
+pub fn iq_to_f32(samples: &[Complex<i16>], scale: f32, out: &mut [Complex<f32>]) -> usize {
+    let mut count = 0;
+    for (dst, src) in out.iter_mut().zip(samples) {
+        *dst = Complex::new(f32::from(src.re) * scale, f32::from(src.im) * scale);
+        count += 1;
+    }
+    count
 }
 
 pub struct AccComplex {
@@ -69,3 +155,118 @@ impl Points {
         Self { points }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exact_magnitude(value: Complex<i16>) -> f64 {
+        f64::from(value.re).hypot(f64::from(value.im))
+    }
+
+    #[test]
+    fn fast_magnitude_stays_within_the_error_bound() {
+        for angle in 0..360 {
+            let (sin, cos) = f64::from(angle).to_radians().sin_cos();
+            for magnitude in [10.0, 1000.0, 20000.0, 32767.0] {
+                let value = Complex::new((magnitude * cos) as i16, (magnitude * sin) as i16);
+                let exact = exact_magnitude(value);
+                let error = (f64::from(fast_magnitude(value)) - exact).abs();
+                assert!(error <= 0.0625 * exact + 1.0, "{value:?}: error {error}");
+            }
+        }
+    }
+
+    #[test]
+    fn fast_magnitude_underestimates_on_the_axes() {
+        assert_eq!(fast_magnitude(Complex::new(0, 0)), 0);
+        assert_eq!(fast_magnitude(Complex::new(3200, 0)), 3000);
+        assert_eq!(fast_magnitude(Complex::new(0, -3200)), 3000);
+    }
+
+    #[test]
+    fn magnitude_follows_the_selected_path() {
+        let value = Complex::new(-3000, 4000);
+        #[cfg(feature = "fixed-point")]
+        assert_eq!(magnitude(value), fast_magnitude(value));
+        #[cfg(not(feature = "fixed-point"))]
+        assert_eq!(magnitude(value), 5000.0);
+    }
+
+    #[test]
+    fn fast_magnitude_does_not_overflow() {
+        let value = Complex::new(i16::MIN, i16::MIN);
+        assert_eq!(fast_magnitude(value), 46080);
+    }
+
+    /// Interleaved IQ pairs `(1, -2)`, `(300, -32768)` in native byte order, in a `u32` aligned
+    /// buffer followed by four zero bytes, along with the samples they encode.
+    fn iq_bytes() -> ([u32; 3], [Complex<i16>; 2]) {
+        let samples = [Complex::new(1, -2), Complex::new(300, i16::MIN)];
+        let mut words = [0u32; 3];
+        let bytes = unsafe { core::slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, 12) };
+        for (chunk, sample) in bytes.chunks_exact_mut(4).zip(samples) {
+            chunk[..2].copy_from_slice(&sample.re.to_ne_bytes());
+            chunk[2..].copy_from_slice(&sample.im.to_ne_bytes());
+        }
+        (words, samples)
+    }
+
+    fn as_bytes(words: &[u32; 3]) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(words.as_ptr() as *const u8, 12) }
+    }
+
+    #[test]
+    fn iq_samples_reinterprets_aligned_buffers() {
+        let (words, samples) = iq_bytes();
+        let bytes = &as_bytes(&words)[..8];
+        let view = iq_samples(bytes).unwrap();
+        assert_eq!(view, samples);
+        assert_eq!(view.as_ptr() as *const u8, bytes.as_ptr());
+    }
+
+    #[test]
+    fn iq_samples_rejects_misaligned_or_partial_buffers() {
+        let (words, _) = iq_bytes();
+        let bytes = as_bytes(&words);
+        assert_eq!(iq_samples(&bytes[1..9]), None);
+        assert_eq!(iq_samples(&bytes[..6]), None);
+        assert_eq!(iq_samples(&[]), Some(&[][..]));
+    }
+
+    #[test]
+    fn copy_iq_samples_handles_any_alignment() {
+        let (words, samples) = iq_bytes();
+        let mut shifted = [0u8; 9];
+        shifted[1..].copy_from_slice(&as_bytes(&words)[..8]);
+
+        let mut out = [Complex::new(0, 0); 2];
+        assert_eq!(copy_iq_samples(&shifted[1..], &mut out), 2);
+        assert_eq!(out, samples);
+    }
+
+    #[test]
+    fn copy_iq_samples_stops_at_the_shorter_buffer() {
+        let (words, samples) = iq_bytes();
+        let bytes = as_bytes(&words);
+
+        let mut out = [Complex::new(0, 0); 4];
+        assert_eq!(copy_iq_samples(&bytes[..10], &mut out), 2);
+        assert_eq!(out[..2], samples);
+        assert_eq!(out[2..], [Complex::new(0, 0); 2]);
+
+        let mut out = [Complex::new(0, 0); 1];
+        assert_eq!(copy_iq_samples(bytes, &mut out), 1);
+        assert_eq!(out, samples[..1]);
+    }
+
+    #[test]
+    fn iq_to_f32_scales_each_sample() {
+        let (_, samples) = iq_bytes();
+        let mut out = [Complex::new(0.0, 0.0); 3];
+        assert_eq!(iq_to_f32(&samples, 1.0 / 32768.0, &mut out), 2);
+        assert_eq!(out[0], Complex::new(1.0 / 32768.0, -2.0 / 32768.0));
+        assert_eq!(out[1], Complex::new(300.0 / 32768.0, -1.0));
+        assert_eq!(out[2], Complex::new(0.0, 0.0));
+    }
+}
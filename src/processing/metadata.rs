@@ -1,4 +1,5 @@
 use a121_sys::{acc_processing_metadata_t, ACC_MAX_NUM_SUBSWEEPS};
+use core::time::Duration;
 
 pub struct ProcessingMetaData {
     inner: acc_processing_metadata_t,
@@ -54,4 +55,41 @@ impl ProcessingMetaData {
     pub fn max_sweep_rate(&self) -> f32 {
         self.inner.max_sweep_rate
     }
+
+    /// Returns the shortest period of a frame of `sweeps_per_frame` sweeps measured at most at
+    /// the maximum sweep rate, `None` if the SDK reports no usable maximum sweep rate.
+    pub fn min_frame_period(&self, sweeps_per_frame: u16) -> Option<Duration> {
+        min_frame_period(self.max_sweep_rate(), sweeps_per_frame)
+    }
+}
+
+/// Returns the shortest period of a frame of `sweeps_per_frame` sweeps measured at most at
+/// `max_sweep_rate` Hz.
+///
+/// Returns `None` if the SDK reports no usable maximum sweep rate.
+pub(crate) fn min_frame_period(max_sweep_rate: f32, sweeps_per_frame: u16) -> Option<Duration> {
+    if !max_sweep_rate.is_finite() || max_sweep_rate <= 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f32(f32::from(sweeps_per_frame) / max_sweep_rate).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_frame_period_from_sweep_rate() {
+        assert_eq!(
+            min_frame_period(1000.0, 10),
+            Some(Duration::from_millis(10))
+        );
+    }
+
+    #[test]
+    fn min_frame_period_rejects_unusable_rates() {
+        for rate in [0.0, -1.0, f32::NAN, f32::INFINITY, f32::MIN_POSITIVE] {
+            assert_eq!(min_frame_period(rate, 1), None, "rate {rate}");
+        }
+    }
 }
@@ -1,5 +1,13 @@
+// State transitions hand the radar back in the error so that it is not lost on failure
+#![allow(clippy::result_large_err)]
+
+use alloc::boxed::Box;
 use core::fmt::{Debug, Display, Formatter};
+use core::future::Future;
+use core::hint::spin_loop;
 use core::marker::PhantomData;
+use core::pin::pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
 use embedded_hal::digital::OutputPin;
 
 use embedded_hal::spi::{ErrorKind as SpiErrorKind, SpiDevice};
@@ -7,18 +15,30 @@ use embedded_hal_async::delay::DelayNs;
 use embedded_hal_async::digital::Wait;
 
 use crate::config::RadarConfig;
-use crate::hal::AccHalImpl;
+use crate::hal::{take_spi_error, AccHalImpl};
 use crate::processing::Processing;
-use crate::sensor::calibration::CalibrationResult;
+use crate::sensor::calibration::{CalibrationResult, CalibrationSettings};
 use crate::sensor::error::SensorError;
 use crate::sensor::Sensor;
-use a121_sys::{acc_sensor_connected, acc_sensor_id_t, acc_sensor_t, acc_version_get_hex};
+use a121_sys::{
+    acc_rss_assembly_test_create, acc_rss_assembly_test_destroy,
+    acc_rss_assembly_test_disable_all_tests, acc_rss_assembly_test_enable,
+    acc_rss_assembly_test_execute, acc_rss_assembly_test_get_results,
+    acc_rss_assembly_test_test_id_t_ACC_RSS_ASSEMBLY_TEST_ID_CLOCK_AND_SUPPLY,
+    acc_rss_test_integration_status_t_ACC_RSS_TEST_INTEGRATION_STATUS_OK,
+    acc_rss_test_integration_status_t_ACC_RSS_TEST_INTEGRATION_STATUS_TIMEOUT,
+    acc_rss_test_state_t_ACC_RSS_TEST_STATE_COMPLETE,
+    acc_rss_test_state_t_ACC_RSS_TEST_STATE_TOGGLE_ENABLE_PIN,
+    acc_rss_test_state_t_ACC_RSS_TEST_STATE_WAIT_FOR_INTERRUPT, acc_sensor_connected,
+    acc_sensor_id_t, acc_sensor_t, acc_version_get_hex, ACC_RSS_ASSEMBLY_TEST_MIN_BUFFER_SIZE,
+};
 
 pub type TransitionResult<STATEOK, STATERR, SINT, ENABLE, DLY> =
     Result<Radar<STATEOK, SINT, ENABLE, DLY>, TransitionError<STATERR, SINT, ENABLE, DLY>>;
 
 pub struct Enabled;
 pub struct Ready;
+/// The sensor is hibernating with its enable pin low, see [`Radar::hibernate_on`].
 pub struct Hibernating;
 
 pub trait RadarState {}
@@ -75,10 +95,79 @@ where
     sensor: Sensor<ENABLE, DLY>,
     pub processing: Processing,
     pub(crate) interrupt: SINT,
-    _hal: AccHalImpl,
+    wait_strategy: WaitStrategy,
+    hal: Option<AccHalImpl>,
     _state: PhantomData<STATE>,
 }
 
+/// How the radar waits for the sensor interrupt after starting a measurement.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, defmt::Format)]
+pub enum WaitStrategy {
+    /// Await the interrupt pin, the executor is free to run other tasks
+    #[default]
+    Async,
+    /// Poll the interrupt pin in a busy loop up to `polls` times before awaiting it.
+    ///
+    /// Avoids the executor wake-up latency when the measurement completes shortly after it was
+    /// started, at the cost of keeping the core busy while spinning.
+    SpinThenAsync {
+        /// Number of polls before falling back to awaiting the pin
+        polls: u32,
+    },
+}
+
+async fn wait_for_interrupt<SINT: Wait>(
+    interrupt: &mut SINT,
+    wait_strategy: WaitStrategy,
+) -> Result<(), SINT::Error> {
+    let mut wait = pin!(interrupt.wait_for_high());
+    if let WaitStrategy::SpinThenAsync { polls } = wait_strategy {
+        let waker = noop_waker();
+        let mut cx = Context::from_waker(&waker);
+        for _ in 0..polls {
+            if let Poll::Ready(result) = wait.as_mut().poll(&mut cx) {
+                return result;
+            }
+            spin_loop();
+        }
+    }
+    wait.await
+}
+
+/// Returns whether the interrupt line is high, by polling a wait for it once.
+fn interrupt_is_high<SINT: Wait>(interrupt: &mut SINT) -> bool {
+    let waker = noop_waker();
+    let mut cx = Context::from_waker(&waker);
+    matches!(
+        pin!(interrupt.wait_for_high()).poll(&mut cx),
+        Poll::Ready(Ok(()))
+    )
+}
+
+/// Waker doing nothing, for futures polled in a busy loop.
+pub(crate) fn noop_waker() -> Waker {
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(|_| RAW, |_| {}, |_| {}, |_| {});
+    const RAW: RawWaker = RawWaker::new(core::ptr::null(), &VTABLE);
+    unsafe { Waker::from_raw(RAW) }
+}
+
+/// Fails with [`SensorError::SpiTransfer`] if an SPI transfer failed since the last check.
+fn check_spi_error() -> Result<(), SensorError> {
+    match take_spi_error() {
+        Some(_) => Err(SensorError::SpiTransfer),
+        None => Ok(()),
+    }
+}
+
+/// Supply health of the sensor, see [`Radar::supply_status`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, defmt::Format)]
+pub enum SupplyStatus {
+    /// The clock and the supply of the sensor are within their operating range.
+    Ok,
+    /// The clock or the supply of the sensor is out of its operating range.
+    Fault,
+}
+
 /// Radar Sensor Software Version
 /// 0xMMMMmmPP where M is major, m is minor and P is patch
 #[derive(Debug)]
@@ -134,8 +223,77 @@ where
     {
         enable_pin.set_high().unwrap();
         delay.delay_ms(2).await;
-        let hal = AccHalImpl::new(spi);
+        let hal = AccHalImpl::new(id, spi);
         hal.register();
+        Self::with_hal(id, Some(hal), interrupt, enable_pin, delay)
+    }
+
+    /// Creates a radar that also uses a 16-bit SPI device for the transfers the SDK makes in
+    /// 16-bit words, see [`AccHalImpl::with_spi16`].
+    pub async fn new_with_spi16<SPI, SPI16>(
+        id: u32,
+        spi: &'static mut SPI,
+        spi16: &'static mut SPI16,
+        interrupt: SINT,
+        mut enable_pin: ENABLE,
+        mut delay: DLY,
+    ) -> Radar<Enabled, SINT, ENABLE, DLY>
+    where
+        SPI: SpiDevice<u8, Error = SpiErrorKind> + Send + 'static,
+        SPI16: SpiDevice<u16, Error = SpiErrorKind> + Send + 'static,
+    {
+        enable_pin.set_high().unwrap();
+        delay.delay_ms(2).await;
+        let hal = AccHalImpl::new(id, spi).with_spi16(spi16);
+        hal.register();
+        Self::with_hal(id, Some(hal), interrupt, enable_pin, delay)
+    }
+
+    /// Creates a radar taking ownership of the SPI device.
+    ///
+    /// The device is moved to the heap and leaked to get the `'static` lifetime the HAL needs,
+    /// so no `static` has to be set up by the caller. The device is registered for sensor `id`,
+    /// one radar can be created per sensor up to [`MAX_SENSORS`](crate::hal::MAX_SENSORS).
+    /// Every call leaks the device it was given, so this is meant for radars created once at
+    /// startup rather than recreated at runtime.
+    pub async fn new_owned<SPI>(
+        id: u32,
+        spi: SPI,
+        interrupt: SINT,
+        enable_pin: ENABLE,
+        delay: DLY,
+    ) -> Radar<Enabled, SINT, ENABLE, DLY>
+    where
+        SPI: SpiDevice<u8, Error = SpiErrorKind> + Send + 'static,
+    {
+        let spi = Box::leak(Box::new(spi));
+        Self::new(id, spi, interrupt, enable_pin, delay).await
+    }
+
+    /// Creates a radar using a HAL that was already registered with the SDK.
+    ///
+    /// Unlike [`Radar::new`], no [`AccHalImpl`] is created or registered, whatever HAL was
+    /// registered last is used. This allows injecting a test HAL, or keeping one registration
+    /// across several radar instances. [`Radar::set_max_spi_transfer_size`] has no effect on a
+    /// radar created this way.
+    pub async fn from_registered_hal(
+        id: u32,
+        interrupt: SINT,
+        mut enable_pin: ENABLE,
+        mut delay: DLY,
+    ) -> Radar<Enabled, SINT, ENABLE, DLY> {
+        enable_pin.set_high().unwrap();
+        delay.delay_ms(2).await;
+        Self::with_hal(id, None, interrupt, enable_pin, delay)
+    }
+
+    fn with_hal(
+        id: u32,
+        hal: Option<AccHalImpl>,
+        interrupt: SINT,
+        enable_pin: ENABLE,
+        delay: DLY,
+    ) -> Radar<Enabled, SINT, ENABLE, DLY> {
         let config = RadarConfig::default();
         let sensor = Sensor::new(id, enable_pin, delay).expect("Failed to create sensor");
         let processing = Processing::new(&config);
@@ -143,9 +301,10 @@ where
             id,
             config,
             interrupt,
+            wait_strategy: WaitStrategy::default(),
             sensor,
             processing,
-            _hal: hal,
+            hal,
             _state: PhantomData,
         }
     }
@@ -166,7 +325,8 @@ where
                 sensor: self.sensor,
                 processing: self.processing,
                 interrupt: self.interrupt,
-                _hal: self._hal,
+                wait_strategy: self.wait_strategy,
+                hal: self.hal,
                 _state: PhantomData,
             })
         } else {
@@ -178,13 +338,103 @@ where
     }
 }
 
+/// Work buffer of the assembly test, aligned for the SDK that keeps its state in it.
+#[repr(align(8))]
+struct AssemblyTestBuffer([u8; ACC_RSS_ASSEMBLY_TEST_MIN_BUFFER_SIZE as usize]);
+
+impl<SINT, ENABLE, DLY> Radar<Enabled, SINT, ENABLE, DLY>
+where
+    SINT: Wait,
+    ENABLE: OutputPin,
+    DLY: DelayNs,
+{
+    /// Checks the clock and the supply of the sensor.
+    ///
+    /// Runs the clock and supply test of the SDK assembly test, which reports whether the supply
+    /// of the sensor is within its operating range but not the voltage itself. A
+    /// [`SupplyStatus::Fault`] while detection degrades points to the power supply, such as a low
+    /// battery, rather than to the processing.
+    ///
+    /// The test resets the sensor, which loses its calibration, calibrate it again afterwards.
+    /// Fails with [`SensorError::AssemblyTestFailed`] if the test could not be run.
+    ///
+    /// The 4 KiB work buffer of the test is held across awaits, so it adds 4 KiB to the size of
+    /// the returned future.
+    pub async fn supply_status(&mut self) -> Result<SupplyStatus, SensorError> {
+        let mut buf = AssemblyTestBuffer([0; ACC_RSS_ASSEMBLY_TEST_MIN_BUFFER_SIZE as usize]);
+        let test = unsafe {
+            acc_rss_assembly_test_create(
+                self.id as acc_sensor_id_t,
+                buf.0.as_mut_ptr().cast(),
+                buf.0.len() as u32,
+            )
+        };
+        if test.is_null() {
+            return Err(SensorError::AssemblyTestFailed);
+        }
+        unsafe {
+            acc_rss_assembly_test_disable_all_tests(test);
+            acc_rss_assembly_test_enable(
+                test,
+                acc_rss_assembly_test_test_id_t_ACC_RSS_ASSEMBLY_TEST_ID_CLOCK_AND_SUPPLY,
+            );
+        }
+
+        take_spi_error();
+        let mut integration_status =
+            acc_rss_test_integration_status_t_ACC_RSS_TEST_INTEGRATION_STATUS_OK;
+        loop {
+            let state = unsafe { acc_rss_assembly_test_execute(test, integration_status) };
+            integration_status =
+                acc_rss_test_integration_status_t_ACC_RSS_TEST_INTEGRATION_STATUS_OK;
+            if state == acc_rss_test_state_t_ACC_RSS_TEST_STATE_COMPLETE {
+                break;
+            } else if state == acc_rss_test_state_t_ACC_RSS_TEST_STATE_TOGGLE_ENABLE_PIN {
+                self.sensor.reset_sensor().await;
+            } else if state == acc_rss_test_state_t_ACC_RSS_TEST_STATE_WAIT_FOR_INTERRUPT
+                && wait_for_interrupt(&mut self.interrupt, self.wait_strategy)
+                    .await
+                    .is_err()
+            {
+                integration_status =
+                    acc_rss_test_integration_status_t_ACC_RSS_TEST_INTEGRATION_STATUS_TIMEOUT;
+            }
+        }
+
+        let mut num_results = 0u16;
+        let results = unsafe { acc_rss_assembly_test_get_results(test, &mut num_results) };
+        let passed = (!results.is_null() && num_results > 0).then(|| {
+            unsafe { core::slice::from_raw_parts(results, num_results as usize) }
+                .iter()
+                .all(|result| result.test_result)
+        });
+        unsafe { acc_rss_assembly_test_destroy(test) };
+        self.sensor.reset_sensor().await;
+        check_spi_error()?;
+
+        match passed {
+            Some(true) => Ok(SupplyStatus::Ok),
+            Some(false) => Ok(SupplyStatus::Fault),
+            None => Err(SensorError::AssemblyTestFailed),
+        }
+    }
+}
+
 impl<SINT, ENABLE, DLY> Radar<Hibernating, SINT, ENABLE, DLY>
 where
     SINT: Wait,
     ENABLE: OutputPin,
     DLY: DelayNs,
 {
-    pub fn hibernate_off(self) -> TransitionResult<Ready, Hibernating, SINT, ENABLE, DLY> {
+    /// Wakes the sensor up from hibernation, it is ready to measure again with its configuration
+    /// and calibration.
+    ///
+    /// The enable pin is set high before the sensor state is restored. On failure the radar is
+    /// handed back with the sensor enabled, reset it and calibrate it again.
+    pub async fn hibernate_off(
+        mut self,
+    ) -> TransitionResult<Ready, Hibernating, SINT, ENABLE, DLY> {
+        self.sensor.enable_sensor().await;
         if self.sensor.hibernate_off().is_ok() {
             Ok(Radar {
                 id: self.id,
@@ -192,7 +442,8 @@ where
                 sensor: self.sensor,
                 processing: self.processing,
                 interrupt: self.interrupt,
-                _hal: self._hal,
+                wait_strategy: self.wait_strategy,
+                hal: self.hal,
                 _state: PhantomData,
             })
         } else {
@@ -210,27 +461,107 @@ where
     ENABLE: OutputPin,
     DLY: DelayNs,
 {
+    /// Measures one frame into `data`.
+    ///
+    /// Only the start of the measurement and the reading of the frame block the executor, both
+    /// are SPI transfers made synchronously by the SDK through the HAL callbacks, which cannot
+    /// yield. The sweeps themselves run on the sensor while the interrupt is awaited, so a long
+    /// measurement with a high HWAAS lets other tasks run. A [`WaitStrategy::SpinThenAsync`]
+    /// wait spins instead for its polls, and a large frame on a slow SPI bus makes the read long,
+    /// split the sweeps over several frames to shorten it.
+    ///
+    /// Fails with [`SensorError::InterruptStuck`] if the interrupt line was high before the
+    /// measurement was started and is still high once the frame was read. The line is then stuck
+    /// high and every wait returns at once, so the frame does not hold a new measurement. Reset
+    /// the sensor, calibrate it and prepare it again to recover.
     pub async fn measure<'a>(&mut self, data: &mut [u8]) -> Result<(), SensorError> {
-        if (self.sensor.measure(&mut self.interrupt).await).is_ok() {
-            if self.sensor.read(data).is_ok() {
-                Ok(())
-            } else {
-                Err(SensorError::ReadError)
+        let high_before = interrupt_is_high(&mut self.interrupt);
+        self.start_measurement()?;
+        self.wait_ready().await?;
+        self.read_frame(data)?;
+        if high_before && interrupt_is_high(&mut self.interrupt) {
+            return Err(SensorError::InterruptStuck);
+        }
+        Ok(())
+    }
+
+    /// Starts a measurement without waiting for it to complete.
+    ///
+    /// Together with [`Radar::wait_ready`] and [`Radar::read_frame`] this is the split form of
+    /// [`Radar::measure`], for callers that want to schedule the wait themselves. Fails with
+    /// [`SensorError::MeasurementInProgress`] if the previous measurement has not been waited
+    /// for yet.
+    pub fn start_measurement(&mut self) -> Result<(), SensorError> {
+        take_spi_error();
+        let result = self.sensor.start_measurement();
+        if let Err(error) = check_spi_error() {
+            if result.is_ok() {
+                // The SDK reported the start as successful, the measurement is still not in
+                // flight and nothing would wait for it to clear the flag
+                drop(self.sensor.measurement_guard());
             }
-        } else {
-            Err(SensorError::MeasurementError)
+            return Err(error);
+        }
+        result
+    }
+
+    /// Returns a future that resolves once the sensor signals that the started measurement is
+    /// complete.
+    ///
+    /// The future only borrows the interrupt pin, it can be combined with timeouts or other
+    /// futures. However the future ends, completed, failed or dropped before completion, the
+    /// measurement is no longer tracked as in flight, see [`Radar::is_measuring`]. After a failed
+    /// or cancelled wait the frame must not be read, start a new measurement instead.
+    pub fn wait_ready(&mut self) -> impl Future<Output = Result<(), SensorError>> + '_ {
+        let interrupt = &mut self.interrupt;
+        // Taken before the future is first polled, so that dropping it unpolled also clears the
+        // flag
+        let measuring = self.sensor.measurement_guard();
+        let wait_strategy = self.wait_strategy;
+        async move {
+            let _measuring = measuring;
+            wait_for_interrupt(interrupt, wait_strategy)
+                .await
+                .map_err(|_| SensorError::MeasurementError)
         }
     }
 
-    pub fn hibernate_on(mut self) -> TransitionResult<Hibernating, Ready, SINT, ENABLE, DLY> {
+    /// Returns whether a measurement is in flight.
+    ///
+    /// Set when a measurement is started, and cleared once the [`Radar::wait_ready`] future ends,
+    /// whether the measurement completed, the wait failed or the future was dropped, or when the
+    /// sensor is power cycled.
+    pub fn is_measuring(&self) -> bool {
+        self.sensor.is_measuring()
+    }
+
+    /// Reads the data of a completed measurement into `data`.
+    ///
+    /// Must only be called once [`Radar::wait_ready`] has resolved.
+    pub fn read_frame(&mut self, data: &mut [u8]) -> Result<(), SensorError> {
+        take_spi_error();
+        let result = self.sensor.read(data).map_err(|_| SensorError::ReadError);
+        check_spi_error()?;
+        result
+    }
+
+    /// Puts the sensor into hibernation, keeping its configuration and calibration.
+    ///
+    /// The enable pin is set low once the sensor state is saved, the sensor then draws a fraction
+    /// of its idle current, see the A121 datasheet for the figures. Waking it up with
+    /// [`Radar::hibernate_off`] is much faster than powering it on, calibrating and preparing it
+    /// again. A measurement cannot be made while hibernating.
+    pub async fn hibernate_on(mut self) -> TransitionResult<Hibernating, Ready, SINT, ENABLE, DLY> {
         if self.sensor.hibernate_on().is_ok() {
+            self.sensor.disable_sensor().await;
             Ok(Radar {
                 id: self.id,
                 config: self.config,
                 sensor: self.sensor,
                 processing: self.processing,
                 interrupt: self.interrupt,
-                _hal: self._hal,
+                wait_strategy: self.wait_strategy,
+                hal: self.hal,
                 _state: PhantomData,
             })
         } else {
@@ -253,15 +584,123 @@ where
         self.id
     }
 
+    /// Sets how [`Radar::wait_ready`], and therefore [`Radar::measure`], wait for the measurement
+    /// to complete.
+    pub fn set_wait_strategy(&mut self, wait_strategy: WaitStrategy) {
+        self.wait_strategy = wait_strategy;
+    }
+
+    /// Returns how the radar waits for a measurement to complete.
+    pub fn wait_strategy(&self) -> WaitStrategy {
+        self.wait_strategy
+    }
+
+    /// Sets the interrupt handling used by [`Radar::calibrate`].
+    ///
+    /// Use a settle time and retries if calibration fails intermittently because of an unstable
+    /// interrupt line. The defaults keep the plain single wait per calibration step.
+    pub fn set_calibration_settings(&mut self, settings: CalibrationSettings) {
+        self.sensor.set_calibration_settings(settings);
+    }
+
+    /// Returns the interrupt handling used by [`Radar::calibrate`].
+    pub fn calibration_settings(&self) -> CalibrationSettings {
+        self.sensor.calibration_settings()
+    }
+
     pub async fn calibrate(&mut self) -> Result<CalibrationResult, SensorError> {
         let mut buf = [0u8; 5560];
-        self.sensor.calibrate(&mut self.interrupt, &mut buf).await
+        take_spi_error();
+        let result = self.sensor.calibrate(&mut self.interrupt, &mut buf).await;
+        check_spi_error()?;
+        result
+    }
+
+    /// Restores a calibration stored with [`CalibrationResult::serialize`], to skip calibrating
+    /// the sensor after a reboot.
+    ///
+    /// See [`CalibrationResult::deserialize`] for the checks done on `buf`. The calibration
+    /// must come from this sensor, calibrate again if the temperature changed since it was
+    /// stored.
+    pub fn restore_calibration(&self, buf: &[u8]) -> Result<CalibrationResult, SensorError> {
+        CalibrationResult::deserialize(buf)
+    }
+
+    /// Sets the minimum time in milliseconds the sensor stays disabled when it is power cycled,
+    /// by [`Radar::reset_sensor`] and before each calibration.
+    ///
+    /// Defaults to 2 ms. Re-enabling the sensor too quickly may leave it
+    /// in a state that is not fully reset.
+    pub fn set_min_off_time_ms(&mut self, min_off_time_ms: u32) {
+        self.sensor.set_min_off_time_ms(min_off_time_ms);
+    }
+
+    /// Returns the minimum time in milliseconds the sensor stays disabled when power cycled.
+    pub fn min_off_time_ms(&self) -> u32 {
+        self.sensor.min_off_time_ms()
+    }
+
+    /// Limits the size of a single SPI transfer requested by the SDK.
+    ///
+    /// Use this when the SPI peripheral or its DMA cannot transfer a whole frame at once, the
+    /// SDK then reads large frames in chunks of at most `max_spi_transfer_size` bytes. The value
+    /// is raised to [`MIN_SPI_TRANSFER_SIZE`](crate::hal::MIN_SPI_TRANSFER_SIZE) if smaller.
+    /// The HAL is registered again with the new limit, prepare the sensor again afterwards.
+    ///
+    /// Has no effect if the radar was created with [`Radar::from_registered_hal`].
+    pub fn set_max_spi_transfer_size(&mut self, max_spi_transfer_size: u16) {
+        if let Some(hal) = self.hal.as_mut() {
+            hal.set_max_spi_transfer_size(max_spi_transfer_size);
+            hal.register();
+        }
+    }
+
+    /// Returns the largest SPI transfer, in bytes, the SDK may request in one call.
+    ///
+    /// `None` if the radar was created with [`Radar::from_registered_hal`].
+    pub fn max_spi_transfer_size(&self) -> Option<u16> {
+        self.hal.as_ref().map(AccHalImpl::max_spi_transfer_size)
     }
 
     pub async fn reset_sensor(&mut self) {
         self.sensor.reset_sensor().await;
     }
 
+    /// Power cycles the sensor to recover it from a bad state, such as after a brownout.
+    ///
+    /// The enable pin is held low for the minimum off time before it is set high again, and the
+    /// HAL is registered again if the radar owns it. The sensor loses its calibration and its
+    /// prepared configuration, the radar is handed back in the [`Enabled`] state so that it is
+    /// calibrated and prepared again before measuring.
+    pub async fn reset(mut self) -> Radar<Enabled, SINT, ENABLE, DLY> {
+        self.sensor.reset_sensor().await;
+        if let Some(hal) = &self.hal {
+            hal.register();
+        }
+        Radar {
+            id: self.id,
+            config: self.config,
+            sensor: self.sensor,
+            processing: self.processing,
+            interrupt: self.interrupt,
+            wait_strategy: self.wait_strategy,
+            hal: self.hal,
+            _state: PhantomData,
+        }
+    }
+
+    /// Powers the sensor on through the enable pin.
+    #[cfg(any(feature = "distance", feature = "presence"))]
+    pub(crate) async fn power_on(&mut self) {
+        self.sensor.enable_sensor().await;
+    }
+
+    /// Powers the sensor off through the enable pin, it loses its configuration.
+    #[cfg(any(feature = "distance", feature = "presence"))]
+    pub(crate) async fn power_off(&mut self) {
+        self.sensor.disable_sensor().await;
+    }
+
     /// Checks if a sensor is connected and responsive.
     ///
     /// Note that the sensor must be powered on before calling this function.
@@ -295,3 +734,69 @@ pub fn rss_version() -> RssVersion {
     let version = unsafe { acc_version_get_hex() };
     RssVersion::new(version)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_sdk::{self, FAKE_TRANSFER_LEN};
+    use crate::test_support::{block_on, FakeDelay, FakeInterrupt, FakePin, FakeSpi, HalState};
+    use core::time::Duration;
+
+    type FakeRadar<STATE> = Radar<STATE, FakeInterrupt, FakePin, FakeDelay>;
+
+    /// Returns a radar with the transfer numbered `fail_at` failing, and its enable pin and delay.
+    fn enabled_radar(fail_at: Option<usize>) -> (FakeRadar<Enabled>, FakePin, FakeDelay) {
+        let (spi, _) = FakeSpi::leaked_failing_at(&[0; FAKE_TRANSFER_LEN], fail_at);
+        let (enable_pin, delay) = (FakePin::default(), FakeDelay::default());
+        let radar = block_on(Radar::new(
+            0,
+            spi,
+            FakeInterrupt,
+            enable_pin.clone(),
+            delay.clone(),
+        ));
+        (radar, enable_pin, delay)
+    }
+
+    fn ready_radar(fail_at: Option<usize>) -> FakeRadar<Ready> {
+        let (radar, _, _) = enabled_radar(fail_at);
+        let Ok(radar) = radar.prepare_sensor(&mut CalibrationResult::default()) else {
+            panic!("the fake sensor is always prepared");
+        };
+        radar
+    }
+
+    #[test]
+    fn measure_recovers_from_a_failed_transfer() {
+        // A measurement makes one transfer to start and one to read the frame
+        for fail_at in 0..2 {
+            let _hal = HalState::lock();
+            let mut radar = ready_radar(Some(fail_at));
+            let mut data = [0u8; 8];
+
+            let result = block_on(radar.measure(&mut data));
+            assert!(
+                matches!(result, Err(SensorError::SpiTransfer)),
+                "transfer {fail_at} failing gave {result:?}"
+            );
+            assert!(!radar.is_measuring());
+            assert!(block_on(radar.measure(&mut data)).is_ok());
+        }
+    }
+
+    #[test]
+    fn reset_power_cycles_the_sensor() {
+        let _hal = HalState::lock();
+        let (mut radar, enable_pin, delay) = enabled_radar(None);
+        radar.set_min_off_time_ms(5);
+
+        block_on(radar.reset());
+
+        assert_eq!(*enable_pin.levels.lock().unwrap(), [true, false, true]);
+        assert_eq!(
+            *delay.delays.lock().unwrap(),
+            [2, 5, 2].map(Duration::from_millis)
+        );
+        assert_eq!(fake_sdk::registrations(), 2, "the HAL is registered again");
+    }
+}
@@ -7,7 +7,7 @@ use embedded_hal_async::delay::DelayNs;
 
 use embedded_hal_async::digital::Wait;
 
-use calibration::CalibrationResult;
+use calibration::{CalibrationResult, CalibrationSettings};
 use error::SensorError;
 
 use crate::config::RadarConfig;
@@ -16,6 +16,9 @@ use a121_sys::*;
 pub mod calibration;
 pub mod error;
 
+/// Default time in milliseconds the enable pin is held low when power cycling the sensor
+pub(crate) const DEFAULT_MIN_OFF_TIME_MS: u32 = 2;
+
 struct InnerSensor {
     inner: *mut acc_sensor_t,
 }
@@ -53,6 +56,25 @@ impl DerefMut for InnerSensor {
     }
 }
 
+/// Clears the in-flight flag of a sensor when dropped, see [`Sensor::measurement_guard`].
+pub(super) struct MeasurementGuard<'a, ENABLE, DLY>
+where
+    ENABLE: OutputPin,
+    DLY: DelayNs,
+{
+    sensor: &'a mut Sensor<ENABLE, DLY>,
+}
+
+impl<ENABLE, DLY> Drop for MeasurementGuard<'_, ENABLE, DLY>
+where
+    ENABLE: OutputPin,
+    DLY: DelayNs,
+{
+    fn drop(&mut self) {
+        self.sensor.measuring = false;
+    }
+}
+
 pub(super) struct Sensor<ENABLE, DLY>
 where
     ENABLE: OutputPin,
@@ -61,6 +83,9 @@ where
     inner: InnerSensor,
     enable_pin: ENABLE,
     dly: DLY,
+    calibration_settings: CalibrationSettings,
+    min_off_time_ms: u32,
+    measuring: bool,
 }
 
 impl<ENABLE, DLY> Sensor<ENABLE, DLY>
@@ -85,6 +110,9 @@ where
             inner,
             enable_pin,
             dly: delay,
+            calibration_settings: CalibrationSettings::default(),
+            min_off_time_ms: DEFAULT_MIN_OFF_TIME_MS,
+            measuring: false,
         })
     }
 
@@ -99,15 +127,57 @@ where
     }
 
     pub async fn disable_sensor(&mut self) {
+        self.measuring = false;
         self.enable_pin.set_low().unwrap();
-        self.dly.delay_ms(2).await;
+        self.dly.delay_ms(self.min_off_time_ms).await;
+    }
+
+    /// Sets the time the enable pin is held low before the sensor can be enabled again.
+    pub fn set_min_off_time_ms(&mut self, min_off_time_ms: u32) {
+        self.min_off_time_ms = min_off_time_ms;
+    }
+
+    /// Returns the time the enable pin is held low before the sensor can be enabled again.
+    pub fn min_off_time_ms(&self) -> u32 {
+        self.min_off_time_ms
+    }
+
+    /// Sets the interrupt handling used while calibrating.
+    pub fn set_calibration_settings(&mut self, settings: CalibrationSettings) {
+        self.calibration_settings = settings;
+    }
+
+    /// Returns the interrupt handling used while calibrating.
+    pub fn calibration_settings(&self) -> CalibrationSettings {
+        self.calibration_settings
     }
 
     /// Calibrates the sensor asynchronously.
+    ///
+    /// A failed attempt is restarted from a sensor reset as many times as configured in the
+    /// calibration settings.
     pub async fn calibrate<SINT: Wait>(
         &mut self,
         interrupt: &mut SINT,
         buffer: &mut [u8],
+    ) -> Result<CalibrationResult, SensorError> {
+        let mut attempts_left = self.calibration_settings.retries;
+        loop {
+            match self.calibrate_once(interrupt, buffer).await {
+                Ok(calibration_result) => return Ok(calibration_result),
+                Err(e) if attempts_left == 0 => return Err(e),
+                Err(_) => {
+                    trace!("Calibration failed, retrying");
+                    attempts_left -= 1;
+                }
+            }
+        }
+    }
+
+    async fn calibrate_once<SINT: Wait>(
+        &mut self,
+        interrupt: &mut SINT,
+        buffer: &mut [u8],
     ) -> Result<CalibrationResult, SensorError> {
         let mut calibration_complete: bool = false;
         let mut calibration_result = CalibrationResult::new();
@@ -136,13 +206,32 @@ where
             }
 
             // Wait for the interrupt signal asynchronously
+            self.wait_calibration_interrupt(interrupt).await?;
+        }
+
+        Ok(calibration_result)
+    }
+
+    /// Waits for the interrupt during calibration, confirming the level after the settle time
+    /// if one is configured.
+    async fn wait_calibration_interrupt<SINT: Wait>(
+        &mut self,
+        interrupt: &mut SINT,
+    ) -> Result<(), SensorError> {
+        interrupt
+            .wait_for_high()
+            .await
+            .map_err(|_| SensorError::CalibrationFailed)?;
+        let settle_time_us = self.calibration_settings.settle_time_us;
+        if settle_time_us > 0 {
+            self.dly.delay_us(settle_time_us).await;
+            // Resolves immediately if the line is still high, waits for the real edge otherwise
             interrupt
                 .wait_for_high()
                 .await
-                .expect("Failed to wait for interrupt");
+                .map_err(|_| SensorError::CalibrationFailed)?;
         }
-
-        Ok(calibration_result)
+        Ok(())
     }
 
     ///
@@ -211,7 +300,7 @@ where
 
     /// Prepares the sensor for entering hibernation.
     ///
-    /// Should be invoked prior to calling `acc_hal_integration_sensor_disable()`.
+    /// Should be invoked prior to disabling the sensor.
     ///
     /// # Returns
     /// `Ok(())` if preparation was successful, `Err(SensorHibernationError)` otherwise.
@@ -229,7 +318,7 @@ where
 
     /// Restores the sensor after exiting hibernation.
     ///
-    /// Should be invoked after calling `acc_hal_integration_sensor_enable()`.
+    /// Should be invoked after enabling the sensor.
     ///
     /// # Returns
     /// `Ok(())` if unpreparation was successful, `Err(SensorHibernationError)` otherwise.
@@ -251,45 +340,44 @@ where
     /// set up and prepared in advance. Ensure the sensor is powered on and calibration and
     /// preparation steps have been completed before calling this function.
     ///
+    /// The measurement is complete once the sensor interrupt goes high, the data can then be
+    /// fetched with `read`.
+    ///
     /// # Preconditions
     ///
     /// - The sensor must be powered on.
     /// - `calibrate` must have been successfully called.
     /// - `prepare` must have been successfully called.
     ///
-    /// # Arguments
-    ///
-    /// * `sensor` - The sensor instance to use for measurement.
-    ///
     /// # Returns
     ///
     /// `Ok(())` if the measurement was successfully started, `Err(SensorError)` otherwise.
-    ///
-    /// # Example
-    ///
-    /// ```no_run
-    /// # use embedded_hal_async::digital::Wait;
-    /// use rad_hard_sys::sensor::*;
-    /// use rad_hard_sys::sensor::error::SensorError;
-    ///  async fn foo<SINT: Wait>(sensor: &mut Sensor<Ready, SINT>) -> Result<(), SensorError> {
-    /// sensor.measure().await?;
-    /// # Ok(())
-    /// # }
-    /// ```
-    pub async fn measure<SINT: Wait>(&mut self, mut interrupt: SINT) -> Result<(), SensorError> {
-        // Implementation to start the radar measurement
+    pub fn start_measurement(&mut self) -> Result<(), SensorError> {
+        if self.measuring {
+            return Err(SensorError::MeasurementInProgress);
+        }
         let success = unsafe { acc_sensor_measure(self.inner.deref_mut()) };
         if success {
-            interrupt
-                .wait_for_high()
-                .await
-                .expect("Failed to wait for interrupt");
+            self.measuring = true;
             Ok(())
         } else {
             Err(SensorError::MeasurementError)
         }
     }
 
+    /// Returns whether a measurement was started and its completion has not been seen yet.
+    pub fn is_measuring(&self) -> bool {
+        self.measuring
+    }
+
+    /// Returns a guard marking the started measurement as no longer in flight when dropped.
+    ///
+    /// Held while waiting for the measurement, so that the flag is cleared whether the wait
+    /// completes, fails or is cancelled.
+    pub fn measurement_guard(&mut self) -> MeasurementGuard<'_, ENABLE, DLY> {
+        MeasurementGuard { sensor: self }
+    }
+
     /// Reads out radar data from the sensor.
     ///
     /// This function should be called after starting a measurement with `measure`. It reads
@@ -1,7 +1,32 @@
 use crate::sensor::error::SensorError;
 use a121_sys::{
-    acc_cal_info_t, acc_cal_result_t, acc_sensor_get_cal_info, acc_sensor_validate_calibration,
+    acc_alg_basic_util_crc32, acc_cal_info_t, acc_cal_result_t, acc_sensor_get_cal_info,
+    acc_sensor_validate_calibration, acc_version_get_hex,
 };
+use core::mem::size_of;
+
+/// Marks the start of a serialized calibration result, the bytes `A121`.
+const SERIALIZED_MAGIC: u32 = 0x3132_3141;
+/// Size of the calibration data in a serialized calibration result.
+const SERIALIZED_DATA_LEN: usize = size_of::<acc_cal_result_t>();
+/// Offset of the calibration data, after the magic and the RSS version.
+const SERIALIZED_DATA_OFFSET: usize = 8;
+/// Offset of the CRC32, covering everything before it.
+const SERIALIZED_CRC_OFFSET: usize = SERIALIZED_DATA_OFFSET + SERIALIZED_DATA_LEN;
+
+/// Interrupt handling used while calibrating.
+///
+/// The interrupt line may toggle during calibration. With a non-zero `settle_time_us`, every
+/// interrupt is confirmed by waiting for the line again after the settle time, so a short
+/// glitch is not taken as the sensor being ready. A failed calibration is restarted from a
+/// sensor reset up to `retries` times.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default, defmt::Format)]
+pub struct CalibrationSettings {
+    /// Delay in microseconds before the interrupt level is confirmed, 0 disables confirmation
+    pub settle_time_us: u32,
+    /// Number of times a failed calibration is restarted
+    pub retries: u8,
+}
 
 pub struct CalibrationInfo {
     inner: acc_cal_info_t,
@@ -65,6 +90,70 @@ impl CalibrationResult {
         }
     }
 
+    /// Size in bytes of a serialized calibration result.
+    pub const SERIALIZED_LEN: usize = SERIALIZED_CRC_OFFSET + 4;
+
+    /// Returns the size in bytes of the serialized calibration result, see
+    /// [`CalibrationResult::serialize`].
+    pub fn serialized_len(&self) -> usize {
+        Self::SERIALIZED_LEN
+    }
+
+    /// Serializes the calibration result into `buf`, to store it and restore it after a reboot
+    /// with [`CalibrationResult::deserialize`].
+    ///
+    /// The data is stored together with the RSS version and a CRC32, so that a calibration from
+    /// another RSS build or a corrupted one is rejected when restoring it. Returns the number of
+    /// bytes written, or [`SensorError::BufferTooSmall`] if `buf` is shorter than
+    /// [`CalibrationResult::SERIALIZED_LEN`].
+    ///
+    /// A restored calibration is only valid for the same sensor and at a similar temperature,
+    /// see [`CalibrationResult::temperature`].
+    pub fn serialize(&self, buf: &mut [u8]) -> Result<usize, SensorError> {
+        let buf = buf
+            .get_mut(..Self::SERIALIZED_LEN)
+            .ok_or(SensorError::BufferTooSmall)?;
+        let version = unsafe { acc_version_get_hex() };
+        buf[..4].copy_from_slice(&SERIALIZED_MAGIC.to_le_bytes());
+        buf[4..SERIALIZED_DATA_OFFSET].copy_from_slice(&version.to_le_bytes());
+        let data = buf[SERIALIZED_DATA_OFFSET..SERIALIZED_CRC_OFFSET].chunks_exact_mut(4);
+        for (chunk, word) in data.zip(self.inner.data.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        let crc = crc32(&buf[..SERIALIZED_CRC_OFFSET]);
+        buf[SERIALIZED_CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+        Ok(Self::SERIALIZED_LEN)
+    }
+
+    /// Restores a calibration result serialized with [`CalibrationResult::serialize`].
+    ///
+    /// Fails with [`SensorError::BufferTooSmall`] if `buf` is shorter than
+    /// [`CalibrationResult::SERIALIZED_LEN`], and with [`SensorError::CalibrationInvalid`] if it
+    /// does not hold a calibration result, was serialized by another RSS version, is corrupted or
+    /// is rejected by the SDK.
+    pub fn deserialize(buf: &[u8]) -> Result<Self, SensorError> {
+        let buf = buf
+            .get(..Self::SERIALIZED_LEN)
+            .ok_or(SensorError::BufferTooSmall)?;
+        let word = |offset: usize| {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&buf[offset..offset + 4]);
+            u32::from_le_bytes(bytes)
+        };
+        if word(0) != SERIALIZED_MAGIC
+            || word(4) != unsafe { acc_version_get_hex() }
+            || word(SERIALIZED_CRC_OFFSET) != crc32(&buf[..SERIALIZED_CRC_OFFSET])
+        {
+            return Err(SensorError::CalibrationInvalid);
+        }
+        let mut result = Self::new();
+        for (i, word_value) in result.inner.data.iter_mut().enumerate() {
+            *word_value = word(SERIALIZED_DATA_OFFSET + i * 4);
+        }
+        result.validate_calibration()?;
+        Ok(result)
+    }
+
     pub fn temperature(&self) -> Result<i16, SensorError> {
         let mut calibration_info = CalibrationInfo::default();
         let res = unsafe { acc_sensor_get_cal_info(self.ptr(), calibration_info.mut_ptr()) };
@@ -76,6 +165,10 @@ impl CalibrationResult {
     }
 }
 
+fn crc32(data: &[u8]) -> u32 {
+    unsafe { acc_alg_basic_util_crc32(data.as_ptr(), data.len()) }
+}
+
 impl From<CalibrationResult> for CalibrationInfo {
     fn from(calibration_result: CalibrationResult) -> Self {
         let mut calibration_info = CalibrationInfo::default();
@@ -97,3 +190,87 @@ impl Default for CalibrationInfo {
         Self { inner }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::fake_sdk::FAKE_RSS_VERSION;
+
+    fn calibration() -> CalibrationResult {
+        let mut result = CalibrationResult::new();
+        for (i, word) in result.inner.data.iter_mut().enumerate() {
+            *word = 0x0101_0101 * i as u32;
+        }
+        result
+    }
+
+    fn serialized() -> [u8; CalibrationResult::SERIALIZED_LEN] {
+        let mut buf = [0; CalibrationResult::SERIALIZED_LEN];
+        assert!(matches!(calibration().serialize(&mut buf), Ok(len) if len == buf.len()));
+        buf
+    }
+
+    /// Sets the word at `offset` and updates the CRC, so only that word is off.
+    fn with_word(
+        mut buf: [u8; CalibrationResult::SERIALIZED_LEN],
+        offset: usize,
+        word: u32,
+    ) -> [u8; CalibrationResult::SERIALIZED_LEN] {
+        buf[offset..offset + 4].copy_from_slice(&word.to_le_bytes());
+        let crc = crc32(&buf[..SERIALIZED_CRC_OFFSET]);
+        buf[SERIALIZED_CRC_OFFSET..].copy_from_slice(&crc.to_le_bytes());
+        buf
+    }
+
+    #[test]
+    fn serialized_calibration_is_restored() {
+        let mut buf = [0xff; CalibrationResult::SERIALIZED_LEN + 4];
+        assert!(matches!(
+            calibration().serialize(&mut buf),
+            Ok(CalibrationResult::SERIALIZED_LEN)
+        ));
+        let restored = CalibrationResult::deserialize(&buf).unwrap();
+        assert_eq!(restored.inner.data, calibration().inner.data);
+    }
+
+    #[test]
+    fn short_buffers_are_rejected() {
+        let mut buf = [0; CalibrationResult::SERIALIZED_LEN - 1];
+        assert!(matches!(
+            calibration().serialize(&mut buf),
+            Err(SensorError::BufferTooSmall)
+        ));
+        assert!(matches!(
+            CalibrationResult::deserialize(&serialized()[..CalibrationResult::SERIALIZED_LEN - 1]),
+            Err(SensorError::BufferTooSmall)
+        ));
+    }
+
+    #[test]
+    fn wrong_magic_is_rejected() {
+        let buf = with_word(serialized(), 0, SERIALIZED_MAGIC + 1);
+        assert!(matches!(
+            CalibrationResult::deserialize(&buf),
+            Err(SensorError::CalibrationInvalid)
+        ));
+    }
+
+    #[test]
+    fn other_rss_version_is_rejected() {
+        let buf = with_word(serialized(), 4, FAKE_RSS_VERSION + 1);
+        assert!(matches!(
+            CalibrationResult::deserialize(&buf),
+            Err(SensorError::CalibrationInvalid)
+        ));
+    }
+
+    #[test]
+    fn corrupted_data_is_rejected() {
+        let mut buf = serialized();
+        buf[SERIALIZED_DATA_OFFSET] ^= 1;
+        assert!(matches!(
+            CalibrationResult::deserialize(&buf),
+            Err(SensorError::CalibrationInvalid)
+        ));
+    }
+}
@@ -1,3 +1,5 @@
+use crate::config::error::ConfigError;
+
 #[derive(Debug, Copy, Clone, defmt::Format)]
 pub enum SensorError {
     CalibrationFailed,
@@ -12,4 +14,15 @@ pub enum SensorError {
     ResultNotAvailable,
     ProcessingFailed,
     BufferTooSmall,
+    MeasurementInProgress,
+    SpiTransfer,
+    AssemblyTestFailed,
+    InterruptStuck,
+    Config(ConfigError),
+}
+
+impl From<ConfigError> for SensorError {
+    fn from(error: ConfigError) -> Self {
+        SensorError::Config(error)
+    }
 }
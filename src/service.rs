@@ -0,0 +1,2 @@
+/// Sparse IQ service, giving access to the raw IQ data of the sensor
+pub mod sparse_iq;
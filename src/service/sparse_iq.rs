@@ -0,0 +1,266 @@
+//! Sparse IQ Service Module
+//!
+//! Provides the raw complex IQ samples measured by the sensor, for algorithms that are not
+//! covered by the detectors. The samples are processed in place in the measurement buffer and
+//! are handed out without being copied.
+
+use core::ops::Range;
+use core::time::Duration;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+use num::Complex;
+
+use crate::num::{magnitude, Magnitude};
+use crate::processing::metadata::ProcessingMetaData;
+use crate::processing::Processing;
+use crate::radar::{Radar, Ready};
+use crate::sensor::error::SensorError;
+
+/// Sparse IQ service measuring with the configuration of the radar.
+///
+/// The radar must have been prepared with its current configuration, the service reads the frame
+/// layout from it when created. Create a new service after changing and preparing the
+/// configuration again.
+pub struct SparseIqService<'radar, SINT, ENABLE, DLY>
+where
+    SINT: Wait,
+    ENABLE: OutputPin,
+    DLY: DelayNs,
+{
+    /// Reference to the radar system, configured and ready for operation.
+    pub radar: &'radar mut Radar<Ready, SINT, ENABLE, DLY>,
+    processing: Processing,
+}
+
+impl<'radar, SINT, ENABLE, DLY> SparseIqService<'radar, SINT, ENABLE, DLY>
+where
+    SINT: Wait,
+    ENABLE: OutputPin,
+    DLY: DelayNs,
+{
+    /// Creates a sparse IQ service for the prepared configuration of `radar`.
+    pub fn new(radar: &'radar mut Radar<Ready, SINT, ENABLE, DLY>) -> Self {
+        let processing = Processing::new(&radar.config);
+        Self { radar, processing }
+    }
+
+    /// Returns the layout of the frames measured with the configuration.
+    pub fn metadata(&self) -> &ProcessingMetaData {
+        self.processing.metadata()
+    }
+
+    /// Returns the shortest period between two frames that the sensor can sustain with the
+    /// configuration.
+    ///
+    /// The period is derived from the maximum sweep rate in the metadata and the number of
+    /// sweeps per frame. Returns `None` if the SDK reports no maximum sweep rate.
+    pub fn min_frame_period(&self) -> Option<Duration> {
+        self.metadata()
+            .min_frame_period(self.radar.config.sweeps_per_frame())
+    }
+
+    /// Returns the size of the buffer needed to measure a frame.
+    pub fn buffer_size(&self) -> Result<usize, SensorError> {
+        Ok(self.radar.config.config_buffer_size()? as usize)
+    }
+
+    /// Measures one frame into `buffer`, see [`Radar::measure`].
+    pub async fn measure(&mut self, buffer: &mut [u8]) -> Result<(), SensorError> {
+        self.radar.measure(buffer).await
+    }
+
+    /// Processes a frame measured into `buffer` into its IQ samples.
+    ///
+    /// The samples are converted in place, the returned frame borrows `buffer` and the buffer
+    /// must be measured into again before processing it a second time. Fails with
+    /// [`SensorError::ProcessingFailed`] if the processed frame does not fit in `buffer`.
+    pub fn process_data<'a>(&mut self, buffer: &'a mut [u8]) -> Result<IqFrame<'a>, SensorError> {
+        let result = unsafe { *self.processing.execute(buffer).ptr() };
+        let metadata = self.processing.metadata();
+        let len = metadata.frame_data_length();
+        let ptr = result.frame as *const Complex<i16>;
+
+        let buffer_range = buffer.as_ptr_range();
+        let frame_start = ptr as *const u8;
+        let frame_end = frame_start.wrapping_add(len * core::mem::size_of::<Complex<i16>>());
+        if ptr.is_null()
+            || ptr as usize % core::mem::align_of::<Complex<i16>>() != 0
+            || frame_start < buffer_range.start
+            || frame_end > buffer_range.end
+        {
+            return Err(SensorError::ProcessingFailed);
+        }
+        // `Complex<i16>` is `repr(C)` with the same layout as `acc_int16_complex_t`, and the
+        // frame lies within `buffer`, which stays borrowed for the lifetime of the frame
+        let data = unsafe { core::slice::from_raw_parts(ptr, len) };
+
+        let num_subsweeps = self.radar.config.num_subsweep() as usize;
+        Ok(IqFrame {
+            data,
+            sweep_data_length: metadata.sweep_data_length(),
+            subsweeps: core::array::from_fn(|i| {
+                if i < num_subsweeps {
+                    let offset = metadata.subsweep_data_offset(i);
+                    offset..offset + metadata.subsweep_data_length(i)
+                } else {
+                    0..0
+                }
+            }),
+            num_subsweeps,
+            data_saturated: result.data_saturated,
+            frame_delayed: result.frame_delayed,
+            calibration_needed: result.calibration_needed,
+            temperature: result.temperature,
+        })
+    }
+}
+
+/// Frame of IQ samples processed by [`SparseIqService::process_data`].
+///
+/// The samples are ordered by sweep, each sweep holding the points of each subsweep one after
+/// the other.
+pub struct IqFrame<'a> {
+    data: &'a [Complex<i16>],
+    sweep_data_length: usize,
+    subsweeps: [Range<usize>; MAX_NUM_SUBSWEEPS],
+    num_subsweeps: usize,
+    data_saturated: bool,
+    frame_delayed: bool,
+    calibration_needed: bool,
+    temperature: i16,
+}
+
+/// Maximum number of subsweeps of a configuration.
+const MAX_NUM_SUBSWEEPS: usize = a121_sys::ACC_MAX_NUM_SUBSWEEPS as usize;
+
+impl<'a> IqFrame<'a> {
+    /// Returns all the samples of the frame.
+    pub fn data(&self) -> &'a [Complex<i16>] {
+        self.data
+    }
+
+    /// Returns the number of sweeps in the frame.
+    pub fn num_sweeps(&self) -> usize {
+        self.data
+            .len()
+            .checked_div(self.sweep_data_length)
+            .unwrap_or(0)
+    }
+
+    /// Returns the samples of the sweep at `index`, or `None` if there is no such sweep.
+    pub fn sweep(&self, index: usize) -> Option<&'a [Complex<i16>]> {
+        let start = index.checked_mul(self.sweep_data_length)?;
+        self.data.get(start..start + self.sweep_data_length)
+    }
+
+    /// Returns the magnitudes of the samples of the sweep at `index`, or `None` if there is no
+    /// such sweep.
+    ///
+    /// The magnitudes are computed on the fly, see [`magnitude`] for the integer-only path of
+    /// the `fixed-point` feature.
+    pub fn magnitudes(&self, index: usize) -> Option<impl Iterator<Item = Magnitude> + 'a> {
+        Some(self.sweep(index)?.iter().map(|&sample| magnitude(sample)))
+    }
+
+    /// Returns the samples of the subsweep at `subsweep` in the sweep at `sweep`, or `None` if
+    /// there is no such sweep or subsweep.
+    pub fn subsweep(&self, sweep: usize, subsweep: usize) -> Option<&'a [Complex<i16>]> {
+        if subsweep >= self.num_subsweeps {
+            return None;
+        }
+        self.sweep(sweep)?.get(self.subsweeps[subsweep].clone())
+    }
+
+    /// Returns whether the sensor data was saturated, lower the receiver gain if this happens.
+    pub fn data_saturated(&self) -> bool {
+        self.data_saturated
+    }
+
+    /// Returns whether the frame was delayed because it was not read out in time.
+    pub fn frame_delayed(&self) -> bool {
+        self.frame_delayed
+    }
+
+    /// Returns whether the sensor needs to be calibrated again.
+    pub fn calibration_needed(&self) -> bool {
+        self.calibration_needed
+    }
+
+    /// Returns the temperature of the sensor during the measurement, in degrees Celsius.
+    ///
+    /// The absolute accuracy is poor, use it to follow temperature changes.
+    pub fn temperature(&self) -> i16 {
+        self.temperature
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Frame of 2 sweeps, each with a subsweep of 3 points followed by one of 2 points.
+    fn frame(data: &[Complex<i16>]) -> IqFrame<'_> {
+        let mut subsweeps: [Range<usize>; MAX_NUM_SUBSWEEPS] = Default::default();
+        subsweeps[0] = 0..3;
+        subsweeps[1] = 3..5;
+        IqFrame {
+            data,
+            sweep_data_length: 5,
+            subsweeps,
+            num_subsweeps: 2,
+            data_saturated: false,
+            frame_delayed: false,
+            calibration_needed: false,
+            temperature: 25,
+        }
+    }
+
+    fn samples() -> [Complex<i16>; 10] {
+        core::array::from_fn(|i| Complex::new(i as i16, -(i as i16)))
+    }
+
+    #[test]
+    fn frame_is_split_into_sweeps() {
+        let data = samples();
+        let frame = frame(&data);
+        assert_eq!(frame.num_sweeps(), 2);
+        assert_eq!(frame.sweep(0), Some(&data[..5]));
+        assert_eq!(frame.sweep(1), Some(&data[5..]));
+        assert_eq!(frame.sweep(2), None);
+        assert_eq!(frame.sweep(usize::MAX), None);
+    }
+
+    #[test]
+    fn sweeps_are_split_into_subsweeps() {
+        let data = samples();
+        let frame = frame(&data);
+        assert_eq!(frame.subsweep(0, 0), Some(&data[..3]));
+        assert_eq!(frame.subsweep(0, 1), Some(&data[3..5]));
+        assert_eq!(frame.subsweep(1, 0), Some(&data[5..8]));
+        assert_eq!(frame.subsweep(1, 1), Some(&data[8..]));
+        assert_eq!(frame.subsweep(1, 2), None);
+        assert_eq!(frame.subsweep(2, 0), None);
+    }
+
+    #[test]
+    fn magnitudes_of_a_sweep() {
+        let data = samples();
+        let frame = frame(&data);
+        let magnitudes: std::vec::Vec<Magnitude> = frame.magnitudes(1).unwrap().collect();
+        let expected: std::vec::Vec<Magnitude> = data[5..].iter().map(|&s| magnitude(s)).collect();
+        assert_eq!(magnitudes, expected);
+        assert!(frame.magnitudes(2).is_none());
+    }
+
+    #[test]
+    fn empty_sweeps_give_no_sweeps() {
+        let data = samples();
+        let frame = IqFrame {
+            sweep_data_length: 0,
+            ..frame(&data)
+        };
+        assert_eq!(frame.num_sweeps(), 0);
+    }
+}
@@ -0,0 +1,253 @@
+//! Support code for the host tests.
+//!
+//! The radar SDK only ships for embedded targets, the host tests link against empty archives in
+//! its place, see the test job of the CI. The few SDK functions a radar goes through to be
+//! created, prepared and to measure are faked in [`fake_sdk`], a test calling any other one
+//! fails to link.
+
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+use core::alloc::{GlobalAlloc, Layout};
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+use core::cell::Cell;
+use core::convert::Infallible;
+use core::future::{poll_fn, Future};
+use core::pin::pin;
+use core::task::{Context, Poll};
+use core::time::Duration;
+use std::boxed::Box;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::vec::Vec;
+
+use embedded_hal::digital::{ErrorType as PinErrorType, OutputPin};
+use embedded_hal::spi::{ErrorKind as SpiErrorKind, ErrorType, Operation, SpiDevice};
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::digital::Wait;
+
+pub(crate) mod fake_sdk;
+
+// Links a defmt logger for the log calls of the code under test
+use defmt_rtt as _;
+defmt::timestamp!("");
+
+/// Global allocator of the test binary, counting the bytes allocated by each thread.
+///
+/// The HAL forwards the allocations of the SDK to the global allocator with the `rust-alloc`
+/// feature, the counts show that it does and frees what it allocated.
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+struct CountingAllocator;
+
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+std::thread_local! {
+    static ALLOCATED: Cell<usize> = const { Cell::new(0) };
+}
+
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Not counted when the thread-local is already destroyed, at thread exit
+        let _ = ALLOCATED
+            .try_with(|allocated| allocated.set(allocated.get().wrapping_add(layout.size())));
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let _ = ALLOCATED
+            .try_with(|allocated| allocated.set(allocated.get().wrapping_sub(layout.size())));
+        std::alloc::System.dealloc(ptr, layout)
+    }
+}
+
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the bytes allocated by the current thread less the bytes it freed, wrapping around as
+/// a thread can free memory allocated by another one.
+#[cfg(all(feature = "rust-alloc", not(feature = "static-alloc")))]
+pub(crate) fn allocated_bytes() -> usize {
+    ALLOCATED.with(Cell::get)
+}
+
+static HAL_LOCK: Mutex<()> = Mutex::new(());
+
+/// Exclusive use of the global state of the HAL by a test.
+///
+/// The SPI devices, the latched SPI error, the last SDK error and the state of the fake SDK are
+/// shared by the whole test binary. Tests using them hold this for their whole run, the state is
+/// cleared when it is taken and when it is dropped, so every test starts from an empty HAL
+/// whatever sensor IDs it uses.
+pub(crate) struct HalState {
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl HalState {
+    pub(crate) fn lock() -> Self {
+        let lock = HAL_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        crate::hal::clear_state();
+        fake_sdk::clear_state();
+        Self { _lock: lock }
+    }
+}
+
+impl Drop for HalState {
+    fn drop(&mut self) {
+        crate::hal::clear_state();
+        fake_sdk::clear_state();
+    }
+}
+
+/// SPI device recording the words written and answering with `response`.
+///
+/// The transfer numbered `fail_at`, counting from 0, fails with [`SpiErrorKind::Other`] instead.
+pub(crate) struct FakeSpi<W> {
+    written: Arc<Mutex<Vec<W>>>,
+    response: Vec<W>,
+    transfers: usize,
+    fail_at: Option<usize>,
+}
+
+impl<W: Copy + 'static> FakeSpi<W> {
+    /// Returns a device living for the rest of the test binary, as the HAL requires, and the
+    /// words written to it.
+    pub(crate) fn leaked(response: &[W]) -> (&'static mut Self, Arc<Mutex<Vec<W>>>) {
+        Self::leaked_failing_at(response, None)
+    }
+
+    /// Same as [`FakeSpi::leaked`], with the transfer numbered `fail_at` failing.
+    pub(crate) fn leaked_failing_at(
+        response: &[W],
+        fail_at: Option<usize>,
+    ) -> (&'static mut Self, Arc<Mutex<Vec<W>>>) {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let spi = Box::leak(Box::new(Self {
+            written: written.clone(),
+            response: response.to_vec(),
+            transfers: 0,
+            fail_at,
+        }));
+        (spi, written)
+    }
+}
+
+impl<W> ErrorType for FakeSpi<W> {
+    type Error = SpiErrorKind;
+}
+
+impl<W: Copy + 'static> SpiDevice<W> for FakeSpi<W> {
+    fn transaction(&mut self, operations: &mut [Operation<'_, W>]) -> Result<(), Self::Error> {
+        let transfer = self.transfers;
+        self.transfers += 1;
+        if self.fail_at == Some(transfer) {
+            return Err(SpiErrorKind::Other);
+        }
+        for operation in operations {
+            let Operation::TransferInPlace(words) = operation else {
+                panic!("the HAL only transfers in place");
+            };
+            self.written.lock().unwrap().extend_from_slice(words);
+            words.copy_from_slice(&self.response[..words.len()]);
+        }
+        Ok(())
+    }
+}
+
+/// Output pin recording the levels it is set to, `true` for high.
+#[derive(Default, Clone)]
+pub(crate) struct FakePin {
+    pub(crate) levels: Arc<Mutex<Vec<bool>>>,
+}
+
+impl PinErrorType for FakePin {
+    type Error = Infallible;
+}
+
+impl OutputPin for FakePin {
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        self.levels.lock().unwrap().push(false);
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        self.levels.lock().unwrap().push(true);
+        Ok(())
+    }
+}
+
+/// Delay returning at once, recording the delays asked for.
+#[derive(Default, Clone)]
+pub(crate) struct FakeDelay {
+    pub(crate) delays: Arc<Mutex<Vec<Duration>>>,
+}
+
+impl DelayNs for FakeDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.delays
+            .lock()
+            .unwrap()
+            .push(Duration::from_nanos(ns.into()));
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.delays
+            .lock()
+            .unwrap()
+            .push(Duration::from_micros(us.into()));
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.delays
+            .lock()
+            .unwrap()
+            .push(Duration::from_millis(ms.into()));
+    }
+}
+
+/// Interrupt pin of a sensor driven by the fake SDK, high while a measurement is pending.
+#[derive(Default)]
+pub(crate) struct FakeInterrupt;
+
+impl PinErrorType for FakeInterrupt {
+    type Error = Infallible;
+}
+
+impl Wait for FakeInterrupt {
+    async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+        poll_fn(|_| {
+            if fake_sdk::measurement_pending() {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+        unimplemented!("not waited for by the radar")
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!("not waited for by the radar")
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!("not waited for by the radar")
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+        unimplemented!("not waited for by the radar")
+    }
+}
+
+/// Runs `future` to completion.
+///
+/// # Panics
+///
+/// Panics if the future is pending, the fakes never make a test wait.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = crate::radar::noop_waker();
+    match pin!(future).poll(&mut Context::from_waker(&waker)) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("the future would wait forever"),
+    }
+}
@@ -0,0 +1,156 @@
+//! Definitions of the SDK functions a radar goes through, standing in for the SDK in the host
+//! tests.
+//!
+//! The fake sensor makes one SPI transfer of [`FAKE_TRANSFER_LEN`] bytes through the registered
+//! HAL when a measurement is started and another one when it is read, so that the SPI error
+//! handling of the radar can be exercised. A started measurement is pending, and the interrupt
+//! of [`FakeInterrupt`](super::FakeInterrupt) high, until it is read. Every calibration result
+//! is valid for the fake SDK, which reports [`FAKE_RSS_VERSION`].
+
+use core::ffi::c_void;
+use core::ptr::NonNull;
+use std::boxed::Box;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use a121_sys::{
+    acc_cal_result_t, acc_config_t, acc_hal_a121_t, acc_hal_sensor_transfer8_function_t,
+    acc_processing_metadata_t, acc_processing_t, acc_sensor_id_t, acc_sensor_t,
+};
+
+/// Length of the SPI transfers made by the fake sensor.
+pub(crate) const FAKE_TRANSFER_LEN: usize = 4;
+
+/// RSS version reported by the fake SDK.
+pub(crate) const FAKE_RSS_VERSION: u32 = 0x0001_0203;
+
+struct FakeSdk {
+    transfer: acc_hal_sensor_transfer8_function_t,
+    registrations: usize,
+    measurement_pending: bool,
+}
+
+static FAKE_SDK: Mutex<FakeSdk> = Mutex::new(FakeSdk {
+    transfer: None,
+    registrations: 0,
+    measurement_pending: false,
+});
+
+fn fake_sdk() -> MutexGuard<'static, FakeSdk> {
+    FAKE_SDK.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(super) fn clear_state() {
+    let mut sdk = fake_sdk();
+    sdk.transfer = None;
+    sdk.registrations = 0;
+    sdk.measurement_pending = false;
+}
+
+/// Returns whether a measurement was started and not read yet.
+pub(crate) fn measurement_pending() -> bool {
+    fake_sdk().measurement_pending
+}
+
+/// Returns how many times a HAL was registered.
+pub(crate) fn registrations() -> usize {
+    fake_sdk().registrations
+}
+
+/// Makes one transfer through the registered HAL for the sensor behind `sensor`.
+///
+/// # Safety
+///
+/// `sensor` must come from [`acc_sensor_create`].
+unsafe fn sensor_transfer(sensor: *const acc_sensor_t) {
+    let sensor_id = *sensor.cast::<acc_sensor_id_t>();
+    let transfer = fake_sdk().transfer.expect("no HAL registered");
+    let mut buffer = [0u8; FAKE_TRANSFER_LEN];
+    transfer(sensor_id, buffer.as_mut_ptr(), buffer.len());
+}
+
+#[no_mangle]
+extern "C" fn acc_rss_hal_register(hal: *const acc_hal_a121_t) -> bool {
+    let mut sdk = fake_sdk();
+    sdk.transfer = unsafe { (*hal).transfer };
+    sdk.registrations += 1;
+    true
+}
+
+#[no_mangle]
+extern "C" fn acc_config_create() -> *mut acc_config_t {
+    NonNull::dangling().as_ptr()
+}
+
+#[no_mangle]
+extern "C" fn acc_config_destroy(_config: *mut acc_config_t) {}
+
+#[no_mangle]
+extern "C" fn acc_processing_create(
+    _config: *const acc_config_t,
+    _processing_metadata: *mut acc_processing_metadata_t,
+) -> *mut acc_processing_t {
+    NonNull::dangling().as_ptr()
+}
+
+#[no_mangle]
+extern "C" fn acc_processing_destroy(_handle: *mut acc_processing_t) {}
+
+#[no_mangle]
+extern "C" fn acc_sensor_create(sensor_id: acc_sensor_id_t) -> *mut acc_sensor_t {
+    Box::into_raw(Box::new(sensor_id)).cast()
+}
+
+#[no_mangle]
+unsafe extern "C" fn acc_sensor_destroy(sensor: *mut acc_sensor_t) {
+    drop(Box::from_raw(sensor.cast::<acc_sensor_id_t>()));
+}
+
+#[no_mangle]
+extern "C" fn acc_sensor_prepare(
+    _sensor: *mut acc_sensor_t,
+    _config: *const acc_config_t,
+    _cal_result: *const acc_cal_result_t,
+    _buffer: *mut c_void,
+    _buffer_size: u32,
+) -> bool {
+    true
+}
+
+#[no_mangle]
+unsafe extern "C" fn acc_sensor_measure(sensor: *mut acc_sensor_t) -> bool {
+    sensor_transfer(sensor);
+    fake_sdk().measurement_pending = true;
+    true
+}
+
+#[no_mangle]
+unsafe extern "C" fn acc_sensor_read(
+    sensor: *const acc_sensor_t,
+    _buffer: *mut c_void,
+    _buffer_size: u32,
+) -> bool {
+    sensor_transfer(sensor);
+    fake_sdk().measurement_pending = false;
+    true
+}
+
+#[no_mangle]
+extern "C" fn acc_version_get_hex() -> u32 {
+    FAKE_RSS_VERSION
+}
+
+/// CRC-32 as used by zlib, the one the SDK computes.
+#[no_mangle]
+unsafe extern "C" fn acc_alg_basic_util_crc32(input: *const u8, len: usize) -> u32 {
+    let data = core::slice::from_raw_parts(input, len);
+    !data.iter().fold(u32::MAX, |crc, &byte| {
+        (0..8).fold(crc ^ u32::from(byte), |crc, _| {
+            (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+#[no_mangle]
+extern "C" fn acc_sensor_validate_calibration(_cal_result: *const acc_cal_result_t) -> bool {
+    true
+}